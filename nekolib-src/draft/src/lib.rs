@@ -1,6 +1,5 @@
 use inner::doc_inline_reexport;
 
 doc_inline_reexport! {
-    rs01dict_runtime,
     rs01dict_tree,
 }
@@ -0,0 +1,489 @@
+use std::ops::{Range, RangeBounds};
+
+use rank_select::RankSelect;
+use small_rank_select::select_in_word;
+use usize_bounds::UsizeBounds;
+
+const W: usize = u64::BITS as usize;
+
+const LEAF_MAX: usize = 32 * W;
+const LEAF_MIN: usize = LEAF_MAX / 4;
+const BRANCH_MAX: usize = 16;
+const BRANCH_MIN: usize = BRANCH_MAX / 4;
+
+/// 挿入・削除のできるビット列。
+///
+/// 64 bit の word を葉に持つ B-tree で管理し、各操作を $O(\\log(n))$
+/// 時間で行う。葉の中での select は [`select_in_word`] による。
+#[derive(Clone)]
+pub struct DynamicBitVec {
+    root: Node,
+}
+
+#[derive(Clone)]
+enum Node {
+    Leaf(Leaf),
+    Internal(Internal),
+}
+
+#[derive(Clone)]
+struct Leaf {
+    // bits beyond `len` are kept zero.
+    buf: Vec<u64>,
+    len: usize,
+    ones: usize,
+}
+
+#[derive(Clone)]
+struct Internal {
+    children: Vec<Node>,
+    len: usize,
+    ones: usize,
+}
+
+impl Leaf {
+    fn new() -> Self { Self { buf: vec![], len: 0, ones: 0 } }
+
+    fn get(&self, i: usize) -> bool { self.buf[i / W] >> (i % W) & 1 != 0 }
+    fn set(&mut self, i: usize, bit: bool) -> bool {
+        let old = self.get(i);
+        if old != bit {
+            self.buf[i / W] ^= 1 << (i % W);
+            if bit {
+                self.ones += 1;
+            } else {
+                self.ones -= 1;
+            }
+        }
+        old
+    }
+    fn insert(&mut self, i: usize, bit: bool) {
+        if self.len.is_multiple_of(W) {
+            self.buf.push(0);
+        }
+        let (w, o) = (i / W, i % W);
+        for j in (w + 1..self.buf.len()).rev() {
+            self.buf[j] = self.buf[j] << 1 | self.buf[j - 1] >> (W - 1);
+        }
+        let low = self.buf[w] & !(!0 << o);
+        self.buf[w] = low | (self.buf[w] & !0 << o) << 1 | (bit as u64) << o;
+        self.len += 1;
+        self.ones += bit as usize;
+    }
+    fn remove(&mut self, i: usize) -> bool {
+        let (w, o) = (i / W, i % W);
+        let bit = self.get(i);
+        let low = self.buf[w] & !(!0 << o);
+        self.buf[w] = low | (self.buf[w] >> 1 & !0 << o);
+        for j in w + 1..self.buf.len() {
+            self.buf[j - 1] |= (self.buf[j] & 1) << (W - 1);
+            self.buf[j] >>= 1;
+        }
+        self.len -= 1;
+        self.ones -= bit as usize;
+        if self.len.is_multiple_of(W) {
+            self.buf.pop();
+        }
+        bit
+    }
+    fn rank1(&self, i: usize) -> usize {
+        let (w, o) = (i / W, i % W);
+        let whole: u32 = self.buf[..w].iter().map(|x| x.count_ones()).sum();
+        let part =
+            if o == 0 { 0 } else { (self.buf[w] & !(!0 << o)).count_ones() };
+        (whole + part) as usize
+    }
+    fn select<const X: bool>(&self, mut i: usize) -> usize {
+        for (j, &w) in self.buf.iter().enumerate() {
+            let valid = (self.len - j * W).min(W);
+            let w = if X { w } else { !w & (!0 >> (W - valid)) };
+            let count = w.count_ones() as usize;
+            if i < count {
+                return j * W + select_in_word(w, i);
+            }
+            i -= count;
+        }
+        unreachable!();
+    }
+
+    fn split_half(&mut self) -> Self {
+        let buf = self.buf.split_off(self.buf.len() / 2);
+        let len = self.len - self.buf.len() * W;
+        let ones = buf.iter().map(|x| x.count_ones() as usize).sum();
+        self.len -= len;
+        self.ones -= ones;
+        Self { buf, len, ones }
+    }
+    fn merge(&mut self, other: Self) {
+        let o = self.len % W;
+        if o == 0 {
+            self.buf.extend(other.buf);
+        } else {
+            for w in other.buf {
+                *self.buf.last_mut().unwrap() |= w << o;
+                self.buf.push(w >> (W - o));
+            }
+        }
+        self.len += other.len;
+        self.ones += other.ones;
+        self.buf.truncate(self.len.div_ceil(W));
+    }
+}
+
+impl Internal {
+    // (index of the child, the offset of `i` in the child)
+    fn find(&self, mut i: usize) -> (usize, usize) {
+        let last = self.children.len() - 1;
+        for (j, child) in self.children[..last].iter().enumerate() {
+            if i < child.len() {
+                return (j, i);
+            }
+            i -= child.len();
+        }
+        (last, i)
+    }
+    fn split_half(&mut self) -> Self {
+        let children = self.children.split_off(self.children.len() / 2);
+        let len = children.iter().map(|c| c.len()).sum();
+        let ones = children.iter().map(|c| c.ones()).sum();
+        self.len -= len;
+        self.ones -= ones;
+        Self { children, len, ones }
+    }
+    fn merge(&mut self, other: Self) {
+        self.children.extend(other.children);
+        self.len += other.len;
+        self.ones += other.ones;
+    }
+    fn fix_underflow(&mut self, j: usize) {
+        if !self.children[j].is_underfull() || self.children.len() == 1 {
+            return;
+        }
+        let j = if j + 1 < self.children.len() { j } else { j - 1 };
+        let right = self.children.remove(j + 1);
+        self.children[j].merge(right);
+        if self.children[j].is_overfull() {
+            let right = self.children[j].split_half();
+            self.children.insert(j + 1, right);
+        }
+    }
+}
+
+impl Node {
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(leaf) => leaf.len,
+            Node::Internal(internal) => internal.len,
+        }
+    }
+    fn ones(&self) -> usize {
+        match self {
+            Node::Leaf(leaf) => leaf.ones,
+            Node::Internal(internal) => internal.ones,
+        }
+    }
+    fn is_underfull(&self) -> bool {
+        match self {
+            Node::Leaf(leaf) => leaf.len < LEAF_MIN,
+            Node::Internal(internal) => internal.children.len() < BRANCH_MIN,
+        }
+    }
+    fn is_overfull(&self) -> bool {
+        match self {
+            Node::Leaf(leaf) => leaf.len > LEAF_MAX,
+            Node::Internal(internal) => internal.children.len() > BRANCH_MAX,
+        }
+    }
+    fn split_half(&mut self) -> Self {
+        match self {
+            Node::Leaf(leaf) => Node::Leaf(leaf.split_half()),
+            Node::Internal(internal) => Node::Internal(internal.split_half()),
+        }
+    }
+    fn merge(&mut self, other: Self) {
+        match (self, other) {
+            (Node::Leaf(left), Node::Leaf(right)) => left.merge(right),
+            (Node::Internal(left), Node::Internal(right)) => left.merge(right),
+            _ => unreachable!(),
+        }
+    }
+
+    fn get(&self, i: usize) -> bool {
+        match self {
+            Node::Leaf(leaf) => leaf.get(i),
+            Node::Internal(internal) => {
+                let (j, i) = internal.find(i);
+                internal.children[j].get(i)
+            }
+        }
+    }
+    fn set(&mut self, i: usize, bit: bool) -> bool {
+        match self {
+            Node::Leaf(leaf) => leaf.set(i, bit),
+            Node::Internal(internal) => {
+                let (j, i) = internal.find(i);
+                let old = internal.children[j].set(i, bit);
+                internal.ones = internal.ones + bit as usize - old as usize;
+                old
+            }
+        }
+    }
+    fn insert(&mut self, i: usize, bit: bool) -> Option<Self> {
+        match self {
+            Node::Leaf(leaf) => leaf.insert(i, bit),
+            Node::Internal(internal) => {
+                let (j, i) = if i == internal.len {
+                    let last = internal.children.len() - 1;
+                    (last, internal.children[last].len())
+                } else {
+                    internal.find(i)
+                };
+                if let Some(right) = internal.children[j].insert(i, bit) {
+                    internal.children.insert(j + 1, right);
+                }
+                internal.len += 1;
+                internal.ones += bit as usize;
+            }
+        }
+        if self.is_overfull() {
+            Some(self.split_half())
+        } else {
+            None
+        }
+    }
+    fn remove(&mut self, i: usize) -> bool {
+        match self {
+            Node::Leaf(leaf) => leaf.remove(i),
+            Node::Internal(internal) => {
+                let (j, i) = internal.find(i);
+                let bit = internal.children[j].remove(i);
+                internal.fix_underflow(j);
+                internal.len -= 1;
+                internal.ones -= bit as usize;
+                bit
+            }
+        }
+    }
+    fn rank1(&self, i: usize) -> usize {
+        match self {
+            Node::Leaf(leaf) => leaf.rank1(i),
+            Node::Internal(internal) => {
+                let (j, i) = internal.find(i);
+                let ones: usize =
+                    internal.children[..j].iter().map(|c| c.ones()).sum();
+                ones + internal.children[j].rank1(i)
+            }
+        }
+    }
+    fn select<const X: bool>(&self, mut i: usize) -> usize {
+        match self {
+            Node::Leaf(leaf) => leaf.select::<X>(i),
+            Node::Internal(internal) => {
+                let mut offset = 0;
+                for child in &internal.children {
+                    let count = if X {
+                        child.ones()
+                    } else {
+                        child.len() - child.ones()
+                    };
+                    if i < count {
+                        return offset + child.select::<X>(i);
+                    }
+                    i -= count;
+                    offset += child.len();
+                }
+                unreachable!();
+            }
+        }
+    }
+}
+
+impl DynamicBitVec {
+    pub fn new() -> Self { Self { root: Node::Leaf(Leaf::new()) } }
+
+    pub fn len(&self) -> usize { self.root.len() }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    pub fn get(&self, i: usize) -> bool {
+        self.check_index(i);
+        self.root.get(i)
+    }
+    pub fn set(&mut self, i: usize, bit: bool) {
+        self.check_index(i);
+        self.root.set(i, bit);
+    }
+    pub fn insert(&mut self, i: usize, bit: bool) {
+        let len = self.len();
+        if i > len {
+            panic!("insertion index (is {i}) should be <= len (is {len})");
+        }
+        if let Some(right) = self.root.insert(i, bit) {
+            let left =
+                std::mem::replace(&mut self.root, Node::Leaf(Leaf::new()));
+            let (len, ones) =
+                (left.len() + right.len(), left.ones() + right.ones());
+            let children = vec![left, right];
+            self.root = Node::Internal(Internal { children, len, ones });
+        }
+    }
+    pub fn push(&mut self, bit: bool) { self.insert(self.len(), bit) }
+    pub fn remove(&mut self, i: usize) -> bool {
+        self.check_index(i);
+        let bit = self.root.remove(i);
+        if let Node::Internal(internal) = &mut self.root {
+            if internal.children.len() == 1 {
+                self.root = internal.children.pop().unwrap();
+            }
+        }
+        bit
+    }
+
+    pub fn rank1(&self, i: usize) -> usize {
+        self.check_index(i);
+        self.root.rank1(i + 1)
+    }
+    pub fn rank0(&self, i: usize) -> usize { i + 1 - self.rank1(i) }
+
+    pub fn select1(&self, i: usize) -> usize {
+        let ones = self.root.ones();
+        if i >= ones {
+            panic!("the number of ones (is {ones}) should be > index (is {i})");
+        }
+        self.root.select::<true>(i)
+    }
+    pub fn select0(&self, i: usize) -> usize {
+        let zeros = self.len() - self.root.ones();
+        if i >= zeros {
+            panic!(
+                "the number of zeros (is {zeros}) should be > index (is {i})"
+            );
+        }
+        self.root.select::<false>(i)
+    }
+
+    pub fn count1(&self, range: impl RangeBounds<usize>) -> usize {
+        let Range { start, end } = range.to_range(self.len());
+        self.root.rank1(end) - self.root.rank1(start)
+    }
+    pub fn count0(&self, range: impl RangeBounds<usize>) -> usize {
+        let Range { start, end } = range.to_range(self.len());
+        (end - start) - self.count1(start..end)
+    }
+
+    fn check_index(&self, i: usize) {
+        let len = self.len();
+        if i >= len {
+            panic!(
+                "index out of bounds: the len is {len} but the index is {i}"
+            );
+        }
+    }
+}
+
+impl Default for DynamicBitVec {
+    fn default() -> Self { Self::new() }
+}
+
+impl RankSelect for DynamicBitVec {
+    fn len(&self) -> usize { self.len() }
+    fn rank1(&self, i: usize) -> usize { self.rank1(i) }
+    fn rank0(&self, i: usize) -> usize { self.rank0(i) }
+    fn select1(&self, i: usize) -> usize { self.select1(i) }
+    fn select0(&self, i: usize) -> usize { self.select0(i) }
+}
+
+impl From<&[bool]> for DynamicBitVec {
+    fn from(a: &[bool]) -> Self {
+        let mut res = Self::new();
+        for &bit in a {
+            res.push(bit);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    fn check(bv: &DynamicBitVec, naive: &[bool]) {
+        let n = naive.len();
+        assert_eq!(bv.len(), n);
+        let mut ones = vec![];
+        let mut zeros = vec![];
+        let mut count = 0;
+        for (i, &bit) in naive.iter().enumerate() {
+            assert_eq!(bv.get(i), bit);
+            count += bit as usize;
+            assert_eq!(bv.rank1(i), count);
+            assert_eq!(bv.rank0(i), i + 1 - count);
+            (if bit { &mut ones } else { &mut zeros }).push(i);
+        }
+        for (i, &j) in ones.iter().enumerate() {
+            assert_eq!(bv.select1(i), j);
+        }
+        for (i, &j) in zeros.iter().enumerate() {
+            assert_eq!(bv.select0(i), j);
+        }
+        for (l, r) in [(0, n), (n / 3, n / 2), (n / 2, n)] {
+            let expected = naive[l..r].iter().filter(|&&b| b).count();
+            assert_eq!(bv.count1(l..r), expected);
+            assert_eq!(bv.count0(l..r), (r - l) - expected);
+        }
+    }
+
+    #[test]
+    fn sanity_check() {
+        let mut bv = DynamicBitVec::new();
+        let mut naive = vec![];
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut rand = |m: usize| rng.gen_range(0..m);
+
+        // grows up to a few levels, and then shrinks
+        for t in 0..40000 {
+            let n = naive.len();
+            let op = if t < 25000 { rand(5) } else { rand(5) + 2 };
+            match op {
+                0..=2 => {
+                    let (i, bit) = (rand(n + 1), rand(3) == 0);
+                    bv.insert(i, bit);
+                    naive.insert(i, bit);
+                }
+                3 if n > 0 => {
+                    let (i, bit) = (rand(n), rand(2) == 0);
+                    bv.set(i, bit);
+                    naive[i] = bit;
+                }
+                _ if n > 0 => {
+                    let i = rand(n);
+                    assert_eq!(bv.remove(i), naive.remove(i));
+                }
+                _ => {}
+            }
+            if t % 1000 == 0 {
+                check(&bv, &naive);
+            }
+        }
+        check(&bv, &naive);
+    }
+
+    #[test]
+    fn from_slice() {
+        // deep enough to have internal nodes under the root
+        let mut a: Vec<_> =
+            (0..200000).map(|i| i % 3 == 0 || i % 7 == 0).collect();
+        let mut bv = DynamicBitVec::from(a.as_slice());
+        check(&bv, &a);
+        while !a.is_empty() {
+            let i = a.len() * 5 / 7;
+            assert_eq!(bv.remove(i), a.remove(i));
+            if a.len() % 40000 == 0 {
+                check(&bv, &a);
+            }
+        }
+        assert!(bv.is_empty());
+    }
+}
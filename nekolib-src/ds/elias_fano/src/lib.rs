@@ -0,0 +1,279 @@
+use rank_select::RankSelect;
+use small_rank_select::select_in_word;
+
+const W: usize = u64::BITS as usize;
+const BLOCK_WORDS: usize = 8;
+const BLOCK_LEN: usize = BLOCK_WORDS * W;
+const SAMPLE: usize = 512;
+
+/// 広義単調増加な非負整数列の Elias–Fano 表現。
+///
+/// 値が $[0, u)$ に含まれる長さ $n$ の列を、$n(2+\\log_2(u/n))+o(n)$
+/// bits 程度で表す。
+pub struct EliasFano {
+    universe: usize,
+    count: usize,
+    low_len: usize,
+    low: Vec<u64>,
+    high: HighBits,
+}
+
+// select-only bit vector for the upper bits, where ones and zeros are
+// both dense enough.
+struct HighBits {
+    buf: Vec<u64>,
+    // the number of ones before each block, with the sentinel.
+    block: Vec<usize>,
+    sample1: Vec<usize>,
+    sample0: Vec<usize>,
+}
+
+impl EliasFano {
+    pub fn new(a: &[usize], universe: usize) -> Self {
+        assert!(a.windows(2).all(|w| w[0] <= w[1]), "not monotone");
+        assert!(a.last().is_none_or(|&x| x < universe), "out of the universe");
+
+        let count = a.len();
+        let low_len = if count == 0 || universe <= count {
+            0
+        } else {
+            (universe / count).ilog2() as usize
+        };
+
+        let mut high = vec![false; count + (universe >> low_len) + 1];
+        let mut low = vec![0; (count * low_len).div_ceil(W)];
+        for (i, &x) in a.iter().enumerate() {
+            high[(x >> low_len) + i] = true;
+            if low_len > 0 {
+                let (j, o) = (i * low_len / W, i * low_len % W);
+                let x = (x & !(!0 << low_len)) as u64;
+                low[j] |= x << o;
+                if o + low_len > W {
+                    low[j + 1] |= x >> (W - o);
+                }
+            }
+        }
+        let high = HighBits::new(&high);
+        Self { universe, count, low_len, low, high }
+    }
+
+    pub fn universe(&self) -> usize { self.universe }
+
+    /// `i` 番目 (0-indexed) の値を返す。
+    pub fn get(&self, i: usize) -> usize {
+        let count = self.count;
+        if i >= count {
+            panic!(
+                "index out of bounds: the count is {count} but the index is {i}"
+            );
+        }
+        (self.high.select1(i) - i) << self.low_len | self.low_get(i)
+    }
+
+    /// `x` 未満の値の個数を返す。
+    pub fn rank(&self, x: usize) -> usize {
+        let x = x.min(self.universe);
+        let h = x >> self.low_len;
+        let mut lo = if h == 0 { 0 } else { self.high.select0(h - 1) + 1 - h };
+        let mut hi = self.high.select0(h) - h;
+        // values in `lo..hi` share the upper bits with `x`
+        let x_low = x & !(!0 << self.low_len);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.low_get(mid) < x_low {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    /// `x` 以上の最小の値を返す。
+    pub fn next_geq(&self, x: usize) -> Option<usize> {
+        let i = self.rank(x);
+        (i < self.count).then(|| self.get(i))
+    }
+    /// `x` 未満の最大の値を返す。
+    pub fn prev_lt(&self, x: usize) -> Option<usize> {
+        self.rank(x).checked_sub(1).map(|i| self.get(i))
+    }
+
+    fn low_get(&self, i: usize) -> usize {
+        if self.low_len == 0 {
+            return 0;
+        }
+        let (j, o) = (i * self.low_len / W, i * self.low_len % W);
+        let mut res = self.low[j] >> o;
+        if o + self.low_len > W {
+            res |= self.low[j + 1] << (W - o);
+        }
+        (res & !(!0 << self.low_len)) as usize
+    }
+}
+
+impl HighBits {
+    fn new(a: &[bool]) -> Self {
+        let mut buf = vec![0; a.len().div_ceil(W)];
+        let mut block = vec![0];
+        let mut sample1 = vec![];
+        let mut sample0 = vec![];
+        let mut count = [0, 0];
+        for (i, &ai) in a.iter().enumerate() {
+            if count[ai as usize] % SAMPLE == 0 {
+                (if ai { &mut sample1 } else { &mut sample0 })
+                    .push(i / BLOCK_LEN);
+            }
+            count[ai as usize] += 1;
+            if ai {
+                buf[i / W] |= 1 << (i % W);
+            }
+            if (i + 1) % BLOCK_LEN == 0 {
+                block.push(count[1]);
+            }
+        }
+        block.push(count[1]);
+        Self { buf, block, sample1, sample0 }
+    }
+
+    fn select<const X: bool>(&self, i: usize) -> usize {
+        let count = |b: usize| {
+            if X {
+                self.block[b]
+            } else {
+                b * BLOCK_LEN - self.block[b]
+            }
+        };
+        let sample = if X { &self.sample1 } else { &self.sample0 };
+        // the answer lies in the last block in `lo..=hi` preceded by at
+        // most `i` of the bits
+        let mut lo = sample[i / SAMPLE];
+        let mut hi = match sample.get(i / SAMPLE + 1) {
+            Some(&b) => b,
+            None => self.block.len() - 2,
+        };
+        while lo < hi {
+            let mid = hi - (hi - lo) / 2;
+            if count(mid) <= i {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        let b = lo;
+        let mut i = i - count(b);
+        for j in b * BLOCK_WORDS.. {
+            let w = if X { self.buf[j] } else { !self.buf[j] };
+            let c = w.count_ones() as usize;
+            if i < c {
+                return j * W + select_in_word(w, i);
+            }
+            i -= c;
+        }
+        unreachable!();
+    }
+    fn select1(&self, i: usize) -> usize { self.select::<true>(i) }
+    fn select0(&self, i: usize) -> usize { self.select::<false>(i) }
+}
+
+/// 値の集合を長さ `universe` のビット列とみなす。値は狭義単調増加とする。
+impl RankSelect for EliasFano {
+    fn len(&self) -> usize { self.universe }
+    fn rank1(&self, i: usize) -> usize { self.rank(i + 1) }
+    fn select1(&self, i: usize) -> usize { self.get(i) }
+    fn select0(&self, i: usize) -> usize {
+        // the number of ones before the answer
+        let (mut lo, mut hi) = (0, self.count);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.get(mid) - mid <= i {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        i + lo
+    }
+}
+
+impl From<&[bool]> for EliasFano {
+    fn from(a: &[bool]) -> Self {
+        let pos: Vec<_> = (0..a.len()).filter(|&i| a[i]).collect();
+        Self::new(&pos, a.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    #[test]
+    fn sequence() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        for (len, universe) in
+            [(0, 1), (1, 1), (5, 3), (100, 100000), (300, 50)]
+        {
+            let mut a: Vec<_> =
+                (0..len).map(|_| rng.gen_range(0..universe)).collect();
+            a.sort_unstable();
+            let ef = EliasFano::new(&a, universe);
+            assert_eq!(ef.universe(), universe);
+            for (i, &ai) in a.iter().enumerate() {
+                assert_eq!(ef.get(i), ai);
+            }
+            for x in (0..=universe + 1).step_by(universe / 500 + 1) {
+                let rank = a.iter().filter(|&&ai| ai < x).count();
+                assert_eq!(ef.rank(x), rank);
+                assert_eq!(ef.next_geq(x), a.get(rank).copied());
+                assert_eq!(ef.prev_lt(x), rank.checked_sub(1).map(|i| a[i]));
+            }
+        }
+    }
+
+    #[test]
+    fn clustered() {
+        // long runs of ones and zeros in the upper bits
+        let mut a = vec![5; 3000];
+        a.extend((0..3000).map(|i| (1 << 20) + i * 7));
+        let ef = EliasFano::new(&a, 1 << 21);
+        for (i, &ai) in a.iter().enumerate() {
+            assert_eq!(ef.get(i), ai);
+        }
+        assert_eq!(ef.rank(6), 3000);
+        assert_eq!(ef.rank(1 << 20), 3000);
+        assert_eq!(ef.next_geq(6), Some(1 << 20));
+        assert_eq!(ef.prev_lt(1 << 21), Some((1 << 20) + 2999 * 7));
+    }
+
+    #[test]
+    fn rank_select() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        for p in [1, 10, 50, 100] {
+            let a: Vec<_> =
+                (0..5000).map(|_| rng.gen_range(0..100) < p).collect();
+            let ef = EliasFano::from(a.as_slice());
+            assert_eq!(ef.len(), a.len());
+            let mut count = 0;
+            for (i, &ai) in a.iter().enumerate() {
+                count += ai as usize;
+                assert_eq!(ef.rank1(i), count);
+                assert_eq!(ef.rank0(i), i + 1 - count);
+            }
+            let ones: Vec<_> = (0..a.len()).filter(|&i| a[i]).collect();
+            let zeros: Vec<_> = (0..a.len()).filter(|&i| !a[i]).collect();
+            for (i, &j) in ones.iter().enumerate() {
+                assert_eq!(ef.select1(i), j);
+            }
+            for (i, &j) in zeros.iter().enumerate() {
+                assert_eq!(ef.select0(i), j);
+            }
+            assert_eq!(
+                ef.count1(1000..3000),
+                ones.iter().filter(|&&i| (1000..3000).contains(&i)).count()
+            );
+        }
+    }
+}
@@ -1,13 +1,17 @@
-use std::ops::RangeFull;
+use std::ops::Range;
 
+use foldable_queue::StackBlocks;
 use monoid::Monoid;
+use usize_bounds::UsizeBounds;
 
 #[derive(Clone, Eq, PartialEq)]
 pub struct FoldableDeque<M: Monoid> {
     front: Vec<M::Set>,
     front_folded: Vec<M::Set>,
+    front_blocks: StackBlocks<M::Set>,
     back: Vec<M::Set>,
     back_folded: Vec<M::Set>,
+    back_blocks: StackBlocks<M::Set>,
     monoid: M,
 }
 
@@ -19,42 +23,77 @@ where
     where
         M: Default,
     {
-        let monoid = M::default();
+        Self::with_monoid(M::default())
+    }
+    pub fn with_monoid(monoid: M) -> Self {
         Self {
             front: vec![],
             front_folded: vec![monoid.id()],
+            front_blocks: StackBlocks::new(true),
             back: vec![],
             back_folded: vec![monoid.id()],
+            back_blocks: StackBlocks::new(false),
             monoid,
         }
     }
+    pub fn len(&self) -> usize { self.front.len() + self.back.len() }
+    pub fn is_empty(&self) -> bool {
+        self.front.is_empty() && self.back.is_empty()
+    }
+    pub fn front(&self) -> Option<&M::Set> {
+        self.front.last().or_else(|| self.back.first())
+    }
+    pub fn back(&self) -> Option<&M::Set> {
+        self.back.last().or_else(|| self.front.first())
+    }
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &M::Set> + '_ {
+        self.front.iter().rev().chain(self.back.iter())
+    }
     pub fn push_back(&mut self, elt: M::Set) {
         let tmp = self.monoid.op(self.back_folded.last().unwrap(), &elt);
         self.back_folded.push(tmp);
         self.back.push(elt);
+        self.back_blocks.push(&self.monoid, &self.back);
     }
     pub fn push_front(&mut self, elt: M::Set) {
         let tmp = self.monoid.op(&elt, self.front_folded.last().unwrap());
         self.front_folded.push(tmp);
         self.front.push(elt);
+        self.front_blocks.push(&self.monoid, &self.front);
     }
     pub fn pop_back(&mut self) -> Option<M::Set> {
         self.rotate_back();
         let elt = self.back.pop()?;
         self.back_folded.pop().unwrap();
+        self.back_blocks.pop(self.back.len());
         Some(elt)
     }
     pub fn pop_front(&mut self) -> Option<M::Set> {
         self.rotate_front();
         let elt = self.front.pop()?;
         self.front_folded.pop().unwrap();
+        self.front_blocks.pop(self.front.len());
         Some(elt)
     }
-    pub fn fold(&self, _: RangeFull) -> M::Set {
-        self.monoid.op(
-            self.front_folded.last().unwrap(),
-            self.back_folded.last().unwrap(),
-        )
+    pub fn fold(&self, range: impl UsizeBounds) -> M::Set {
+        let Range { start, end } = range.to_range(self.len());
+        let mid = self.front.len();
+
+        // *0123][45678*; front: [3, 2, 1, 0], back: [4, 5, 6, 7, 8]
+        // Ranges touching the boundary `][` are covered by `*_folded`, and
+        // the others fall back to `*_blocks` in O(log(n)) time.
+        if start <= mid && mid <= end {
+            self.monoid.op(
+                &self.front_folded[mid - start],
+                &self.back_folded[end - mid],
+            )
+        } else if end < mid {
+            let range = mid - end..mid - start;
+            self.front_blocks.fold(&self.monoid, &self.front, range)
+        } else {
+            let range = start - mid..end - mid;
+            self.back_blocks.fold(&self.monoid, &self.back, range)
+        }
     }
 
     fn rotate_front(&mut self) {
@@ -105,9 +144,19 @@ where
                 Some(acc.clone())
             },
         ));
+
+        self.front_blocks = StackBlocks::build(&self.monoid, &self.front, true);
+        self.back_blocks = StackBlocks::build(&self.monoid, &self.back, false);
     }
 }
 
+impl<M: Monoid + Default> Default for FoldableDeque<M>
+where
+    M::Set: Clone,
+{
+    fn default() -> Self { Self::new() }
+}
+
 impl<M: Monoid> std::fmt::Debug for FoldableDeque<M>
 where
     M::Set: std::fmt::Debug,
@@ -121,6 +170,8 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::collections::VecDeque;
+
     use concat_monoid::OpConcat;
 
     use crate::FoldableDeque;
@@ -173,6 +224,47 @@ mod tests {
         assert_eq!(queue.fold(..), vec![]);
     }
 
+    #[test]
+    fn fold_range() {
+        let mut queue = FoldableDeque::<OpConcat<_, Vec<_>>>::new();
+        let mut naive = VecDeque::new();
+        let check = |queue: &FoldableDeque<_>, naive: &VecDeque<_>| {
+            let n = naive.len();
+            assert_eq!(queue.len(), n);
+            assert_eq!(queue.front(), naive.front());
+            assert_eq!(queue.back(), naive.back());
+            assert!(queue.iter().eq(naive.iter()));
+            for start in 0..=n {
+                for end in start..=n {
+                    let expected: Vec<_> =
+                        naive.range(start..end).flatten().copied().collect();
+                    assert_eq!(queue.fold(start..end), expected);
+                }
+            }
+        };
+
+        // pushing `i` at the back if `i % 3 != 0`, at the front otherwise,
+        // and popping from both sides so that rotations happen.
+        for i in 0..40 {
+            if i % 3 != 0 {
+                queue.push_back(vec![i]);
+                naive.push_back(vec![i]);
+            } else {
+                queue.push_front(vec![i]);
+                naive.push_front(vec![i]);
+            }
+            check(&queue, &naive);
+        }
+        for i in 0..40 {
+            if i % 5 < 2 {
+                assert_eq!(queue.pop_back(), naive.pop_back());
+            } else {
+                assert_eq!(queue.pop_front(), naive.pop_front());
+            }
+            check(&queue, &naive);
+        }
+    }
+
     #[test]
     fn test_fmt() {
         let mut queue = FoldableDeque::<OpConcat<_, Vec<_>>>::new();
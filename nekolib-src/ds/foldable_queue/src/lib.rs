@@ -1,13 +1,16 @@
-use std::ops::RangeFull;
+use std::ops::Range;
 
 use monoid::Monoid;
+use usize_bounds::UsizeBounds;
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct FoldableQueue<M: Monoid> {
     front: Vec<M::Set>,
     front_folded: Vec<M::Set>,
+    front_blocks: StackBlocks<M::Set>,
     back: Vec<M::Set>,
     back_folded: Vec<M::Set>,
+    back_blocks: StackBlocks<M::Set>,
     monoid: M,
 }
 
@@ -16,31 +19,64 @@ impl<M: Monoid> FoldableQueue<M> {
     where
         M: Default,
     {
-        let monoid = M::default();
+        Self::with_monoid(M::default())
+    }
+    pub fn with_monoid(monoid: M) -> Self {
         Self {
             front: vec![],
             front_folded: vec![monoid.id()],
+            front_blocks: StackBlocks::new(true),
             back: vec![],
             back_folded: vec![monoid.id()],
+            back_blocks: StackBlocks::new(false),
             monoid,
         }
     }
+    pub fn len(&self) -> usize { self.front.len() + self.back.len() }
+    pub fn is_empty(&self) -> bool {
+        self.front.is_empty() && self.back.is_empty()
+    }
+    pub fn front(&self) -> Option<&M::Set> {
+        self.front.last().or_else(|| self.back.first())
+    }
+    pub fn back(&self) -> Option<&M::Set> {
+        self.back.last().or_else(|| self.front.first())
+    }
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &M::Set> + '_ {
+        self.front.iter().rev().chain(self.back.iter())
+    }
     pub fn push(&mut self, elt: M::Set) {
         let tmp = self.monoid.op(self.back_folded.last().unwrap(), &elt);
         self.back_folded.push(tmp);
         self.back.push(elt);
+        self.back_blocks.push(&self.monoid, &self.back);
     }
     pub fn pop(&mut self) -> Option<M::Set> {
         self.rotate();
         let elt = self.front.pop()?;
         self.front_folded.pop().unwrap();
+        self.front_blocks.pop(self.front.len());
         Some(elt)
     }
-    pub fn fold(&self, _: RangeFull) -> M::Set {
-        self.monoid.op(
-            self.front_folded.last().unwrap(),
-            self.back_folded.last().unwrap(),
-        )
+    pub fn fold(&self, range: impl UsizeBounds) -> M::Set {
+        let Range { start, end } = range.to_range(self.len());
+        let mid = self.front.len();
+
+        // *0123][45678*; front: [3, 2, 1, 0], back: [4, 5, 6, 7, 8]
+        // Ranges touching the boundary `][` are covered by `*_folded`, and
+        // the others fall back to `*_blocks` in O(log(n)) time.
+        if start <= mid && mid <= end {
+            self.monoid.op(
+                &self.front_folded[mid - start],
+                &self.back_folded[end - mid],
+            )
+        } else if end < mid {
+            let range = mid - end..mid - start;
+            self.front_blocks.fold(&self.monoid, &self.front, range)
+        } else {
+            let range = start - mid..end - mid;
+            self.back_blocks.fold(&self.monoid, &self.back, range)
+        }
     }
 
     fn rotate(&mut self) {
@@ -53,6 +89,133 @@ impl<M: Monoid> FoldableQueue<M> {
                 .push(self.monoid.op(&elt, self.front_folded.last().unwrap()));
             self.front.push(elt);
         }
+        self.front_blocks = StackBlocks::build(&self.monoid, &self.front, true);
+        self.back_blocks.clear();
+    }
+}
+
+impl<M: Monoid + Default> Default for FoldableQueue<M> {
+    fn default() -> Self { Self::new() }
+}
+
+pub fn sliding_window_fold<M: Monoid>(
+    buf: &[M::Set],
+    k: usize,
+    monoid: M,
+) -> Vec<M::Set>
+where
+    M::Set: Clone,
+{
+    if buf.len() < k {
+        return vec![];
+    }
+    let mut queue = FoldableQueue::with_monoid(monoid);
+    let mut res = Vec::with_capacity(buf.len() - k + 1);
+    for x in &buf[..k] {
+        queue.push(x.clone());
+    }
+    res.push(queue.fold(..));
+    for x in &buf[k..] {
+        queue.push(x.clone());
+        queue.pop();
+        res.push(queue.fold(..));
+    }
+    res
+}
+
+/// スタックの区間 fold を $O(\\log(n))$ 時間で求めるための補助構造。
+///
+/// [`FoldableQueue`] などで、各スタックとともに持つ。`rev` なら、スタックの
+/// top 側を先頭として fold する。
+#[derive(Clone, PartialEq, Eq)]
+pub struct StackBlocks<S> {
+    // `blocks[j][i]` is the fold of `stack[i << (j + 1)..(i + 1) << (j + 1)]`.
+    blocks: Vec<Vec<S>>,
+    rev: bool,
+}
+
+impl<S> StackBlocks<S> {
+    pub fn new(rev: bool) -> Self { Self { blocks: vec![], rev } }
+
+    pub fn build<M: Monoid<Set = S>>(
+        monoid: &M,
+        stack: &[S],
+        rev: bool,
+    ) -> Self {
+        let mut res = Self::new(rev);
+        while stack.len() >> (res.blocks.len() + 1) > 0 {
+            let children = res.blocks.last().map(|v| &v[..]).unwrap_or(stack);
+            let level = children
+                .chunks_exact(2)
+                .map(|c| res.op(monoid, &c[0], &c[1]))
+                .collect();
+            res.blocks.push(level);
+        }
+        res
+    }
+
+    /// `stack` に要素が push された直後に呼ぶ。
+    pub fn push<M: Monoid<Set = S>>(&mut self, monoid: &M, stack: &[S]) {
+        let len = stack.len();
+        let mut j = 0;
+        while len.is_multiple_of(2 << j) {
+            let i = len >> (j + 1);
+            let children = if j == 0 { stack } else { &self.blocks[j - 1] };
+            let tmp =
+                self.op(monoid, &children[2 * i - 2], &children[2 * i - 1]);
+            if self.blocks.len() == j {
+                self.blocks.push(vec![]);
+            }
+            self.blocks[j].push(tmp);
+            j += 1;
+        }
+    }
+
+    /// `stack` から要素が pop された直後に、pop 後の長さを渡して呼ぶ。
+    pub fn pop(&mut self, len: usize) {
+        for (j, level) in self.blocks.iter_mut().enumerate() {
+            let n = len >> (j + 1);
+            if level.len() == n {
+                break;
+            }
+            level.truncate(n);
+        }
+    }
+
+    pub fn clear(&mut self) { self.blocks.clear(); }
+
+    pub fn fold<M: Monoid<Set = S>>(
+        &self,
+        monoid: &M,
+        stack: &[S],
+        Range { start, end }: Range<usize>,
+    ) -> S {
+        let (mut il, mut ir) = (start, end);
+        let (mut resl, mut resr) = (monoid.id(), monoid.id());
+        let mut j = 0;
+        while il < ir {
+            let level = if j == 0 { stack } else { &self.blocks[j - 1] };
+            if il & 1 != 0 {
+                resl = self.op(monoid, &resl, &level[il]);
+                il += 1;
+            }
+            if ir & 1 != 0 {
+                ir -= 1;
+                resr = self.op(monoid, &level[ir], &resr);
+            }
+            il >>= 1;
+            ir >>= 1;
+            j += 1;
+        }
+        self.op(monoid, &resl, &resr)
+    }
+
+    fn op<M: Monoid<Set = S>>(&self, monoid: &M, x: &S, y: &S) -> S {
+        if self.rev {
+            monoid.op(y, x)
+        } else {
+            monoid.op(x, y)
+        }
     }
 }
 
@@ -61,14 +224,14 @@ where
     M::Set: std::fmt::Debug,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_list()
-            .entries(self.front.iter().rev().chain(self.back.iter()))
-            .finish()
+        f.debug_list().entries(self.iter()).finish()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::VecDeque;
+
     use concat_monoid::OpConcat;
 
     use crate::*;
@@ -102,6 +265,53 @@ mod tests {
         assert_eq!(queue.fold(..), vec![]);
     }
 
+    #[test]
+    fn fold_range() {
+        let mut queue = FoldableQueue::<OpConcat<_, Vec<_>>>::new();
+        let mut naive = VecDeque::new();
+        let check = |queue: &FoldableQueue<_>, naive: &VecDeque<_>| {
+            let n = naive.len();
+            assert_eq!(queue.len(), n);
+            assert_eq!(queue.front(), naive.front());
+            assert_eq!(queue.back(), naive.back());
+            assert!(queue.iter().eq(naive.iter()));
+            for start in 0..=n {
+                for end in start..=n {
+                    let expected: Vec<_> =
+                        naive.range(start..end).flatten().copied().collect();
+                    assert_eq!(queue.fold(start..end), expected);
+                }
+            }
+        };
+
+        for i in 0..60 {
+            if i % 4 == 3 {
+                assert_eq!(queue.pop(), naive.pop_front());
+            } else {
+                queue.push(vec![i]);
+                naive.push_back(vec![i]);
+            }
+            check(&queue, &naive);
+        }
+        while !naive.is_empty() {
+            assert_eq!(queue.pop(), naive.pop_front());
+            check(&queue, &naive);
+        }
+    }
+
+    #[test]
+    fn sliding_window() {
+        let a: Vec<_> = (0..10).map(|i| vec![i]).collect();
+        for k in 0..=12 {
+            let monoid = OpConcat::<_, Vec<_>>::default();
+            let actual = sliding_window_fold(&a, k, monoid);
+            let expected: Vec<_> = (0..(a.len() + 1).saturating_sub(k))
+                .map(|i| a[i..i + k].concat())
+                .collect();
+            assert_eq!(actual, expected);
+        }
+    }
+
     #[test]
     fn test_fmt() {
         let mut queue = FoldableQueue::<OpConcat<_, Vec<_>>>::new();
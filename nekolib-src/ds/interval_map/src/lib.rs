@@ -5,11 +5,14 @@ use std::{
     collections::BTreeMap,
     fmt::{self, Debug},
     ops::{
+        Add,
         Bound::{Excluded, Included, Unbounded},
-        RangeBounds,
+        Range, RangeBounds, Sub,
     },
 };
 
+use monoid::Monoid;
+
 #[derive(Clone, Copy, Eq, PartialEq)]
 enum Left<T> {
     NegInfinity,
@@ -433,3 +436,293 @@ impl<K: Ord + Debug, V: Debug> Debug for IntervalMap<K, V> {
         fmt.debug_map().entries(self.inner.iter()).finish()
     }
 }
+
+pub trait IntKey: Copy + Ord + Add<Output = Self> + Sub<Output = Self> {
+    const ZERO: Self;
+    fn is_odd(self) -> bool;
+    fn half(self) -> Self;
+}
+
+macro_rules! impl_int_key {
+    ( $($ty:ty)* ) => { $(
+        impl IntKey for $ty {
+            const ZERO: $ty = 0;
+            fn is_odd(self) -> bool { self & 1 != 0 }
+            fn half(self) -> $ty { self >> 1 }
+        }
+    )* }
+}
+
+impl_int_key! { i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize }
+
+/// 整数の区間から monoid の元への対応づけ。
+///
+/// 区間は半開区間 $[l, r)$ で表す。
+#[derive(Clone)]
+pub struct IntervalFoldMap<K, M: Monoid> {
+    inner: BTreeMap<K, (K, M::Set)>,
+    covered: BTreeMap<M::Set, K>,
+    monoid: M,
+}
+
+impl<K: IntKey, M: Monoid> IntervalFoldMap<K, M>
+where
+    M::Set: Ord + Clone,
+{
+    /// $S\gets\emptyset$ で初期化する。
+    pub fn new() -> Self
+    where
+        M: Default,
+    {
+        Self::with_monoid(M::default())
+    }
+    pub fn with_monoid(monoid: M) -> Self {
+        Self { inner: BTreeMap::new(), covered: BTreeMap::new(), monoid }
+    }
+
+    /// $S=\emptyset$ を返す。
+    pub fn is_empty(&self) -> bool { self.inner.is_empty() }
+
+    pub fn len(&self) -> usize { self.inner.len() }
+
+    /// 区間 `b` 中の各 $k$ に対して $S\xleftarrow{\cup} (k\mapsto v)$ で更新する。
+    pub fn insert(&mut self, b: Range<K>, v: M::Set) {
+        self.insert_with(b, v, |_, _| {});
+    }
+
+    /// [`insert`](Self::insert) と同様に更新する。上書きされる各区間と
+    /// その値に対して `f` を呼ぶ。
+    pub fn insert_with(
+        &mut self,
+        b: Range<K>,
+        v: M::Set,
+        mut f: impl FnMut(Range<K>, M::Set),
+    ) {
+        let Range { start: mut l, end: mut r } = b;
+        if l >= r {
+            return;
+        }
+        self.remove_internal(l, r, &mut f);
+        self.add_covered(&v, r - l);
+
+        if let Some((&kl, (kr, kv))) = self.inner.range(..l).next_back() {
+            if *kr == l && *kv == v {
+                self.inner.remove(&kl);
+                l = kl;
+            }
+        }
+        if let Some((kr, kv)) = self.inner.get(&r) {
+            if *kv == v {
+                let kr = *kr;
+                self.inner.remove(&r);
+                r = kr;
+            }
+        }
+        self.inner.insert(l, (r, v));
+    }
+
+    /// 区間 `b` 中の各 $k$ に対して $S\xleftarrow{\setminus} (k\mapsto\bullet)$
+    /// で更新する。
+    pub fn remove(&mut self, b: Range<K>) -> Vec<(Range<K>, M::Set)> {
+        let mut res = vec![];
+        let Range { start: l, end: r } = b;
+        if l < r {
+            self.remove_internal(l, r, &mut |it, v| res.push((it, v)));
+        }
+        res
+    }
+
+    /// $S$ 中で $k$ に対応する値を返す。
+    pub fn get(&self, k: K) -> Option<&M::Set> {
+        (self.inner.range(..=k).next_back())
+            .filter(|(_, (r, _))| k < *r)
+            .map(|(_, (_, v))| v)
+    }
+
+    /// 値 $v$ に対応する $k$ の個数を返す。
+    pub fn covered_len(&self, v: &M::Set) -> K {
+        self.covered.get(v).copied().unwrap_or(K::ZERO)
+    }
+
+    /// 区間 `b` 中の各 $k$ に対応する値を $k$ の昇順に fold する。
+    /// 値のない $k$ は単位元として扱う。
+    ///
+    /// `b` と交わる区間の個数を $m$ として $O(m\log(r-l))$ 時間。
+    pub fn fold(&self, b: Range<K>) -> M::Set {
+        let Range { start: l, end: r } = b;
+        let mut res = self.monoid.id();
+        if l >= r {
+            return res;
+        }
+        let first = self.inner.range(..l).next_back();
+        for (&kl, (kr, v)) in first.into_iter().chain(self.inner.range(l..r)) {
+            let (kl, kr) = (kl.max(l), (*kr).min(r));
+            if kl < kr {
+                res = self.monoid.op(&res, &self.pow(v, kr - kl));
+            }
+        }
+        res
+    }
+
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (Range<K>, &M::Set)> + '_ {
+        self.inner.iter().map(|(&l, (r, v))| (l..*r, v))
+    }
+
+    fn pow(&self, v: &M::Set, mut n: K) -> M::Set {
+        let monoid = &self.monoid;
+        let mut res = monoid.id();
+        let mut v = v.clone();
+        while n > K::ZERO {
+            if n.is_odd() {
+                res = monoid.op(&res, &v);
+            }
+            v = monoid.op(&v, &v);
+            n = n.half();
+        }
+        res
+    }
+
+    fn split_at(&mut self, k: K) {
+        // [l, r) => [l, k), [k, r)
+        let Some((&l, (r, _))) = self.inner.range(..k).next_back() else {
+            return;
+        };
+        if k < *r {
+            let (r, v) = self.inner.remove(&l).unwrap();
+            self.inner.insert(l, (k, v.clone()));
+            self.inner.insert(k, (r, v));
+        }
+    }
+
+    fn remove_internal(
+        &mut self,
+        l: K,
+        r: K,
+        f: &mut impl FnMut(Range<K>, M::Set),
+    ) {
+        self.split_at(l);
+        self.split_at(r);
+        let keys: Vec<_> = self.inner.range(l..r).map(|(&k, _)| k).collect();
+        for kl in keys {
+            let (kr, v) = self.inner.remove(&kl).unwrap();
+            self.sub_covered(&v, kr - kl);
+            f(kl..kr, v);
+        }
+    }
+
+    fn add_covered(&mut self, v: &M::Set, len: K) {
+        let e = self.covered.entry(v.clone()).or_insert(K::ZERO);
+        *e = *e + len;
+    }
+
+    fn sub_covered(&mut self, v: &M::Set, len: K) {
+        let e = self.covered.get_mut(v).unwrap();
+        *e = *e - len;
+        if *e == K::ZERO {
+            self.covered.remove(v);
+        }
+    }
+}
+
+impl<K: IntKey, M: Monoid + Default> Default for IntervalFoldMap<K, M>
+where
+    M::Set: Ord + Clone,
+{
+    fn default() -> Self { Self::new() }
+}
+
+impl<K: Debug, M: Monoid> Debug for IntervalFoldMap<K, M>
+where
+    M::Set: Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_map()
+            .entries(self.inner.iter().map(|(l, (r, v))| (l..r, v)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use concat_monoid::OpConcat;
+    use op_add::OpAdd;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    #[test]
+    fn fold_map() {
+        let n = 20;
+        let mut map = IntervalFoldMap::<usize, OpConcat<_, Vec<_>>>::new();
+        let mut naive = vec![None; n];
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut rand = |m: usize| rng.gen_range(0..m);
+        for _ in 0..300 {
+            let (l, r) = (rand(n + 1), rand(n + 1));
+            let v = vec![rand(3)];
+            if rand(4) == 0 {
+                let removed = map.remove(l..r);
+                let expected: Vec<_> = (l..r)
+                    .filter_map(|i| naive[i].take().map(|v| (i..i + 1, v)))
+                    .collect();
+                let actual: Vec<_> = removed
+                    .into_iter()
+                    .flat_map(|(it, v)| it.map(move |i| (i..i + 1, v.clone())))
+                    .collect();
+                assert_eq!(actual, expected);
+            } else {
+                let mut overwritten = vec![];
+                map.insert_with(l..r, v.clone(), |it, v| {
+                    overwritten.extend(it.map(|i| (i, v.clone())))
+                });
+                let expected: Vec<_> = (l..r)
+                    .filter_map(|i| naive[i].replace(v.clone()).map(|v| (i, v)))
+                    .collect();
+                assert_eq!(overwritten, expected);
+            }
+
+            for (i, x) in naive.iter().enumerate() {
+                assert_eq!(map.get(i), x.as_ref());
+            }
+            for v in 0..3 {
+                let len = naive.iter().filter(|x| **x == Some(vec![v])).count();
+                assert_eq!(map.covered_len(&vec![v]), len);
+            }
+            for l in 0..=n {
+                for r in l..=n {
+                    let expected: Vec<_> = naive[l..r]
+                        .iter()
+                        .flatten()
+                        .flatten()
+                        .copied()
+                        .collect();
+                    assert_eq!(map.fold(l..r), expected);
+                }
+            }
+            // adjacent intervals with the same value are connected
+            let its: Vec<_> = map.iter().collect();
+            assert!(its
+                .windows(2)
+                .all(|w| w[0].0.end < w[1].0.start || w[0].1 != w[1].1));
+        }
+    }
+
+    #[test]
+    fn chtholly() {
+        // assigns `v` to `a[l..r]` and returns the sum before assignment
+        let mut map = IntervalFoldMap::<i64, OpAdd<i64>>::new();
+        map.insert(0..10, 1);
+        let mut sum = 0;
+        map.insert_with(3..7, 5, |it, v| sum += (it.end - it.start) * v);
+        assert_eq!(sum, 4);
+        assert_eq!(map.fold(0..10), 26);
+        assert_eq!(map.fold(2..4), 6);
+        assert_eq!(map.covered_len(&1), 6);
+        assert_eq!(map.covered_len(&5), 4);
+        assert_eq!(map.covered_len(&2), 0);
+        assert_eq!(format!("{map:?}"), "{0..3: 1, 3..7: 5, 7..10: 1}");
+    }
+}
@@ -0,0 +1,464 @@
+use std::{
+    collections::VecDeque,
+    ops::{
+        Add,
+        Bound::{Excluded, Included, Unbounded},
+        Mul, Range, RangeBounds, Sub,
+    },
+};
+
+use bisect::Bisect;
+use stern_brocot::Fraction;
+
+pub trait Coord<T> {
+    type Output;
+    /// $a\\cdot x+b$ と同じ順序を持つ値を返す。
+    fn key(&self, a: &T, b: &T) -> T;
+    /// `key` の返した値から $a\\cdot x+b$ を復元する。
+    fn value(&self, key: T) -> Self::Output;
+}
+
+macro_rules! impl_coord_int {
+    ( $($ty:ty)* ) => { $(
+        impl Coord<$ty> for $ty {
+            type Output = $ty;
+            fn key(&self, a: &$ty, b: &$ty) -> $ty { a * self + b }
+            fn value(&self, key: $ty) -> $ty { key }
+        }
+    )* }
+}
+
+impl_coord_int! { i8 i16 i32 i64 i128 isize }
+
+// (a * p + b * q) / q for x = p / q with q > 0
+impl<I: Clone> Coord<I> for Fraction<I>
+where
+    for<'a> &'a I: Add<&'a I, Output = I> + Mul<&'a I, Output = I>,
+{
+    type Output = Fraction<I>;
+    fn key(&self, a: &I, b: &I) -> I { &(a * &self.numer) + &(b * &self.denom) }
+    fn value(&self, key: I) -> Fraction<I> {
+        Fraction { numer: key, denom: self.denom.clone() }
+    }
+}
+
+// (a, b) for y = a * x + b
+type Line<T> = (T, T);
+
+#[derive(Clone)]
+pub struct LiChaoTree<T, X = T> {
+    xs: Vec<X>,
+    min: Vec<Option<Line<T>>>,
+    max: Vec<Option<Line<T>>>,
+}
+
+impl<T: Clone + Ord, X: Coord<T> + Ord> From<Vec<X>> for LiChaoTree<T, X> {
+    fn from(mut xs: Vec<X>) -> Self {
+        xs.sort_unstable();
+        xs.dedup();
+        let len = 2 * xs.len().next_power_of_two();
+        Self { xs, min: vec![None; len], max: vec![None; len] }
+    }
+}
+
+impl<T: Clone + Ord, X: Coord<T> + Ord> LiChaoTree<T, X> {
+    pub fn insert_line(&mut self, a: T, b: T) {
+        self.insert_internal(0..self.xs.len(), (a, b), |_, _, _| {});
+    }
+    pub fn insert_segment(&mut self, range: impl RangeBounds<X>, a: T, b: T) {
+        let range = self.index_range(range);
+        self.insert_internal(range, (a, b), |_, _, _| {});
+    }
+    pub fn min_at(&self, x: &X) -> Option<X::Output> { self.fold_at(x, false) }
+    pub fn max_at(&self, x: &X) -> Option<X::Output> { self.fold_at(x, true) }
+
+    fn index_range(&self, range: impl RangeBounds<X>) -> Range<usize> {
+        let xs = &self.xs;
+        let start = match range.start_bound() {
+            Included(x) => xs.partition_point(|y| y < x),
+            Excluded(x) => xs.partition_point(|y| y <= x),
+            Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Included(x) => xs.partition_point(|y| y <= x),
+            Excluded(x) => xs.partition_point(|y| y < x),
+            Unbounded => xs.len(),
+        };
+        start..end.max(start)
+    }
+
+    fn insert_internal(
+        &mut self,
+        Range { start, end }: Range<usize>,
+        line: Line<T>,
+        mut log: impl FnMut(bool, usize, &Option<Line<T>>),
+    ) {
+        let size = self.min.len() / 2;
+        let (mut il, mut ir) = (size + start, size + end);
+        while il < ir {
+            if il & 1 != 0 {
+                self.insert_node(il, line.clone(), &mut log);
+                il += 1;
+            }
+            if ir & 1 != 0 {
+                ir -= 1;
+                self.insert_node(ir, line.clone(), &mut log);
+            }
+            il >>= 1;
+            ir >>= 1;
+        }
+    }
+
+    fn insert_node(
+        &mut self,
+        v: usize,
+        line: Line<T>,
+        log: &mut impl FnMut(bool, usize, &Option<Line<T>>),
+    ) {
+        let size = self.min.len() / 2;
+        let h = v.leading_zeros() - size.leading_zeros();
+        let l = (v << h) - size;
+        let r = l + (1 << h);
+        let xs = &self.xs;
+        for (max, tree) in [(false, &mut self.min), (true, &mut self.max)] {
+            let mut log = |v: usize, old: &Option<Line<T>>| log(max, v, old);
+            insert_node(xs, tree, (v, l, r), line.clone(), max, &mut log);
+        }
+    }
+
+    fn fold_at(&self, x: &X, max: bool) -> Option<X::Output> {
+        let size = self.min.len() / 2;
+        let tree = if max { &self.max } else { &self.min };
+        let i = self.xs.binary_search(x).expect("unregistered coordinate");
+        let mut res: Option<T> = None;
+        let mut v = size + i;
+        while v > 0 {
+            if let Some((a, b)) = &tree[v] {
+                let key = x.key(a, b);
+                let better = |r: &T| if max { &key > r } else { &key < r };
+                if res.as_ref().is_none_or(better) {
+                    res = Some(key);
+                }
+            }
+            v >>= 1;
+        }
+        res.map(|key| x.value(key))
+    }
+}
+
+fn insert_node<T: Clone + Ord, X: Coord<T>>(
+    xs: &[X],
+    tree: &mut [Option<Line<T>>],
+    (mut v, mut l, mut r): (usize, usize, usize),
+    mut line: Line<T>,
+    max: bool,
+    log: &mut impl FnMut(usize, &Option<Line<T>>),
+) {
+    // the points beyond `xs` are regarded as copies of the last one.
+    let x_at = |i: usize| &xs[i.min(xs.len() - 1)];
+    let better = |x: &X, new: &Line<T>, old: &Line<T>| {
+        let (new, old) = (x.key(&new.0, &new.1), x.key(&old.0, &old.1));
+        if max {
+            new > old
+        } else {
+            new < old
+        }
+    };
+    loop {
+        let Some(cur) = &mut tree[v] else {
+            log(v, &None);
+            tree[v] = Some(line);
+            return;
+        };
+        let m = (l + r) / 2;
+        let left = better(x_at(l), &line, cur);
+        let mid = better(x_at(m), &line, cur);
+        if mid {
+            log(v, &Some(cur.clone()));
+            std::mem::swap(cur, &mut line);
+        }
+        if r - l == 1 {
+            return;
+        }
+        if left != mid {
+            (v, r) = (2 * v, m);
+        } else {
+            (v, l) = (2 * v + 1, m);
+        }
+    }
+}
+
+/// 直線の追加を取り消せる [`LiChaoTree`]。
+#[derive(Clone)]
+pub struct UndoLiChaoTree<T, X = T> {
+    tree: LiChaoTree<T, X>,
+    history: Vec<(bool, usize, Option<Line<T>>)>,
+    checkpoints: Vec<usize>,
+}
+
+impl<T: Clone + Ord, X: Coord<T> + Ord> From<Vec<X>> for UndoLiChaoTree<T, X> {
+    fn from(xs: Vec<X>) -> Self {
+        Self { tree: xs.into(), history: vec![], checkpoints: vec![] }
+    }
+}
+
+impl<T: Clone + Ord, X: Coord<T> + Ord> UndoLiChaoTree<T, X> {
+    pub fn insert_line(&mut self, a: T, b: T) {
+        let range = 0..self.tree.xs.len();
+        self.insert_internal(range, (a, b));
+    }
+    pub fn insert_segment(&mut self, range: impl RangeBounds<X>, a: T, b: T) {
+        let range = self.tree.index_range(range);
+        self.insert_internal(range, (a, b));
+    }
+    /// 最後に追加した直線を取り除く。追加されていなかった場合は `false` を返す。
+    pub fn undo(&mut self) -> bool {
+        let Some(len) = self.checkpoints.pop() else { return false };
+        for (max, v, old) in self.history.drain(len..).rev() {
+            let tree =
+                if max { &mut self.tree.max } else { &mut self.tree.min };
+            tree[v] = old;
+        }
+        true
+    }
+    pub fn min_at(&self, x: &X) -> Option<X::Output> { self.tree.min_at(x) }
+    pub fn max_at(&self, x: &X) -> Option<X::Output> { self.tree.max_at(x) }
+
+    fn insert_internal(&mut self, range: Range<usize>, line: Line<T>) {
+        self.checkpoints.push(self.history.len());
+        let history = &mut self.history;
+        self.tree.insert_internal(range, line, |max, v, old| {
+            history.push((max, v, old.clone()))
+        });
+    }
+}
+
+/// 傾きの単調な直線を追加していく convex hull trick。
+///
+/// 最小値のみを扱う。最大値は符号を反転して求める。
+#[derive(Clone)]
+pub struct MonotoneCht<T> {
+    lines: VecDeque<Line<T>>,
+}
+
+impl<T: Clone + Ord> MonotoneCht<T>
+where
+    for<'a> &'a T: Sub<&'a T, Output = T> + Mul<&'a T, Output = T>,
+{
+    pub fn new() -> Self { Self { lines: VecDeque::new() } }
+    pub fn len(&self) -> usize { self.lines.len() }
+    pub fn is_empty(&self) -> bool { self.lines.is_empty() }
+
+    /// 傾きがこれまでのいずれ以下でもある直線を追加する。
+    pub fn push_back(&mut self, a: T, b: T) {
+        if let Some((a1, _)) = self.lines.back() {
+            assert!(&a <= a1, "slopes must be non-increasing");
+        }
+        let line = (a, b);
+        if let Some(last) = self.lines.back() {
+            if last.0 == line.0 {
+                if last.1 <= line.1 {
+                    return;
+                }
+                self.lines.pop_back();
+            }
+        }
+        while self.lines.len() >= 2 {
+            let n = self.lines.len();
+            if !is_redundant(&self.lines[n - 2], &self.lines[n - 1], &line) {
+                break;
+            }
+            self.lines.pop_back();
+        }
+        self.lines.push_back(line);
+    }
+    /// 傾きがこれまでのいずれ以上でもある直線を追加する。
+    pub fn push_front(&mut self, a: T, b: T) {
+        if let Some((a1, _)) = self.lines.front() {
+            assert!(&a >= a1, "slopes must be non-decreasing");
+        }
+        let line = (a, b);
+        if let Some(first) = self.lines.front() {
+            if first.0 == line.0 {
+                if first.1 <= line.1 {
+                    return;
+                }
+                self.lines.pop_front();
+            }
+        }
+        while self.lines.len() >= 2 {
+            if !is_redundant(&line, &self.lines[0], &self.lines[1]) {
+                break;
+            }
+            self.lines.pop_front();
+        }
+        self.lines.push_front(line);
+    }
+
+    pub fn min_at<X: Coord<T>>(&self, x: &X) -> Option<X::Output> {
+        let n = self.lines.len();
+        if n == 0 {
+            return None;
+        }
+        let key = |i: usize| x.key(&self.lines[i].0, &self.lines[i].1);
+        let i = if n == 1 {
+            0
+        } else {
+            (0..n - 1).bisect(|&i| key(i) > key(i + 1))
+        };
+        Some(x.value(key(i)))
+    }
+    /// `x` がこれまでの呼び出しのいずれ以上でもあるとき、ならし $O(1)$
+    /// 時間で最小値を返す。
+    pub fn min_at_increasing<X: Coord<T>>(
+        &mut self,
+        x: &X,
+    ) -> Option<X::Output> {
+        let key = |(a, b): &Line<T>| x.key(a, b);
+        while self.lines.len() >= 2
+            && key(&self.lines[0]) >= key(&self.lines[1])
+        {
+            self.lines.pop_front();
+        }
+        self.lines.front().map(|line| x.value(key(line)))
+    }
+}
+
+impl<T: Clone + Ord> Default for MonotoneCht<T>
+where
+    for<'a> &'a T: Sub<&'a T, Output = T> + Mul<&'a T, Output = T>,
+{
+    fn default() -> Self { Self::new() }
+}
+
+fn is_redundant<T: Ord>(l1: &Line<T>, l2: &Line<T>, l3: &Line<T>) -> bool
+where
+    for<'a> &'a T: Sub<&'a T, Output = T> + Mul<&'a T, Output = T>,
+{
+    // l1.0 > l2.0 > l3.0; l2 is redundant iff x12 >= x23, where
+    // x12 = (b2 - b1) / (a1 - a2) and x23 = (b3 - b2) / (a2 - a3).
+    let ((a1, b1), (a2, b2), (a3, b3)) = (l1, l2, l3);
+    &(b2 - b1) * &(a2 - a3) >= &(b3 - b2) * &(a1 - a2)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+    use stern_brocot::Fraction;
+
+    use crate::*;
+
+    #[test]
+    fn lines_and_segments() {
+        let xs: Vec<i64> = (-20..=20).step_by(3).collect();
+        let mut tree = LiChaoTree::<i64>::from(xs.clone());
+        let mut naive = vec![(None, None); xs.len()];
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        for _ in 0..200 {
+            let a = rng.gen_range(-10..=10);
+            let b = rng.gen_range(-100..=100);
+            let (l, r) = (rng.gen_range(-25..25), rng.gen_range(-25..25));
+            let whole = rng.gen_ratio(1, 4);
+            if whole {
+                tree.insert_line(a, b);
+            } else {
+                tree.insert_segment(l..=r, a, b);
+            }
+            for (i, &x) in xs.iter().enumerate() {
+                if whole || (l..=r).contains(&x) {
+                    let y = a * x + b;
+                    let (lo, hi) = &mut naive[i];
+                    *lo = Some(lo.map_or(y, |lo: i64| lo.min(y)));
+                    *hi = Some(hi.map_or(y, |hi: i64| hi.max(y)));
+                }
+            }
+            for (i, x) in xs.iter().enumerate() {
+                assert_eq!((tree.min_at(x), tree.max_at(x)), naive[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn fraction_coords() {
+        let frac = |numer, denom| Fraction { numer, denom };
+        let xs = vec![frac(-3, 2), frac(1, 3), frac(0, 1), frac(5, 4)];
+        let mut tree = LiChaoTree::<i64, _>::from(xs);
+        tree.insert_line(2, 1);
+        tree.insert_segment(frac(0, 1).., -1, 1);
+        tree.insert_segment(..frac(1, 2), 0, -1);
+
+        assert_eq!(tree.min_at(&frac(-3, 2)), Some(frac(-4, 2)));
+        assert_eq!(tree.max_at(&frac(-3, 2)), Some(frac(-1, 1)));
+        assert_eq!(tree.min_at(&frac(0, 1)), Some(frac(-1, 1)));
+        assert_eq!(tree.max_at(&frac(0, 1)), Some(frac(1, 1)));
+        assert_eq!(tree.min_at(&frac(1, 3)), Some(frac(-1, 1)));
+        assert_eq!(tree.max_at(&frac(1, 3)), Some(frac(5, 3)));
+        assert_eq!(tree.min_at(&frac(5, 4)), Some(frac(-1, 4)));
+        assert_eq!(tree.max_at(&frac(5, 4)), Some(frac(14, 4)));
+    }
+
+    #[test]
+    fn undo() {
+        let xs: Vec<i64> = (0..10).collect();
+        let mut tree = UndoLiChaoTree::<i64>::from(xs.clone());
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut lines = vec![];
+        for _ in 0..100 {
+            if lines.is_empty() || !rng.gen_ratio(1, 3) {
+                let a = rng.gen_range(-10..=10);
+                let b = rng.gen_range(-100..=100);
+                let (l, r) = (rng.gen_range(0..10), rng.gen_range(0..10));
+                tree.insert_segment(l..r, a, b);
+                lines.push((l..r, a, b));
+            } else {
+                assert!(tree.undo());
+                lines.pop();
+            }
+            for x in &xs {
+                let ys = lines
+                    .iter()
+                    .filter(|(range, ..)| range.contains(x))
+                    .map(|(_, a, b)| a * x + b);
+                assert_eq!(tree.min_at(x), ys.clone().min());
+                assert_eq!(tree.max_at(x), ys.max());
+            }
+        }
+        while tree.undo() {}
+        assert_eq!(tree.min_at(&0), None);
+    }
+
+    #[test]
+    fn monotone_cht() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut lines = VecDeque::new();
+        let mut cht = MonotoneCht::new();
+        let (mut lo, mut hi) = (0, 0);
+        for _ in 0..200 {
+            if rng.gen_bool(0.5) {
+                hi += rng.gen_range(0..3);
+                let b = rng.gen_range(-100..=100);
+                cht.push_front(hi, b);
+                lines.push_front((hi, b));
+            } else {
+                lo -= rng.gen_range(0..3);
+                let b = rng.gen_range(-100..=100);
+                cht.push_back(lo, b);
+                lines.push_back((lo, b));
+            }
+            for x in -30..=30 {
+                let expected = lines.iter().map(|(a, b)| a * x + b).min();
+                assert_eq!(cht.min_at(&x), expected);
+            }
+        }
+
+        let mut cht = MonotoneCht::new();
+        for &(a, b) in &lines {
+            cht.push_back(a, b);
+        }
+        for x in -30..=30 {
+            let expected = lines.iter().map(|(a, b)| a * x + b).min();
+            assert_eq!(cht.min_at_increasing(&x), expected);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::ops::{Range, RangeBounds};
+
+use usize_bounds::UsizeBounds;
+
+/// rank/select を持つビット列。
+///
+/// `rank1(i)` は区間 $[0, i]$ 中の `1` の個数、`select1(i)` は `i` 番目
+/// (0-indexed) の `1` の位置を返す。`0` についても同様。
+pub trait RankSelect {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool { self.len() == 0 }
+
+    fn rank1(&self, i: usize) -> usize;
+    fn rank0(&self, i: usize) -> usize { i + 1 - self.rank1(i) }
+    fn select1(&self, i: usize) -> usize;
+    fn select0(&self, i: usize) -> usize;
+
+    fn count1(&self, range: impl RangeBounds<usize>) -> usize {
+        let Range { start, end } = range.to_range(self.len());
+        if start == end {
+            0
+        } else if start == 0 {
+            self.rank1(end - 1)
+        } else {
+            self.rank1(end - 1) - self.rank1(start - 1)
+        }
+    }
+    fn count0(&self, range: impl RangeBounds<usize>) -> usize {
+        let Range { start, end } = range.to_range(self.len());
+        (end - start) - self.count1(start..end)
+    }
+}
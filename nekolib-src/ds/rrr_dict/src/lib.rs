@@ -0,0 +1,279 @@
+use rank_select::RankSelect;
+use small_rank_select::select_in_word;
+
+const W: usize = u64::BITS as usize;
+const BLOCK_LEN: usize = 15;
+const CLASS_LEN: usize = 4;
+const SUPER_BLOCKS: usize = 32;
+
+const BINOM: [[u16; BLOCK_LEN + 1]; BLOCK_LEN + 1] = {
+    let mut res = [[0; BLOCK_LEN + 1]; BLOCK_LEN + 1];
+    let mut n = 0;
+    while n <= BLOCK_LEN {
+        res[n][0] = 1;
+        let mut k = 1;
+        while k <= n {
+            res[n][k] = res[n - 1][k - 1] + res[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    res
+};
+
+// the number of bits to represent an offset of the class.
+const OFFSET_LEN: [usize; BLOCK_LEN + 1] = {
+    let mut res = [0; BLOCK_LEN + 1];
+    let mut k = 0;
+    while k <= BLOCK_LEN {
+        let max = BINOM[BLOCK_LEN][k] - 1;
+        res[k] = (u16::BITS - max.leading_zeros()) as usize;
+        k += 1;
+    }
+    res
+};
+
+/// RRR 符号によって圧縮されたビット列。
+///
+/// $15$ bits ごとのブロックを、`1` の個数 (class) と、同じ class
+/// の中での番号 (offset) で表す。`1` の割合を $p$ として、おおよそ
+/// $nH_0(p)+o(n)$ bits で表される。
+pub struct RrrDict {
+    len: usize,
+    classes: Vec<u64>,
+    offsets: Vec<u64>,
+    // the number of ones before each superblock, with the sentinel.
+    super_ones: Vec<usize>,
+    // the position in `offsets` of each superblock.
+    super_ptr: Vec<usize>,
+}
+
+fn encode(w: u16) -> u16 {
+    let mut res = 0;
+    let mut k = 0;
+    for p in (0..BLOCK_LEN).filter(|&p| w >> p & 1 != 0) {
+        k += 1;
+        res += BINOM[p][k];
+    }
+    res
+}
+
+fn decode(class: usize, mut offset: u16) -> u16 {
+    let mut res = 0;
+    let mut p = BLOCK_LEN;
+    for k in (1..=class).rev() {
+        p -= 1;
+        while BINOM[p][k] > offset {
+            p -= 1;
+        }
+        res |= 1 << p;
+        offset -= BINOM[p][k];
+    }
+    res
+}
+
+fn push_bits(buf: &mut Vec<u64>, len: &mut usize, w: u64, w_len: usize) {
+    if w_len == 0 {
+        return;
+    }
+    if len.is_multiple_of(W) {
+        buf.push(w);
+    } else {
+        buf[*len / W] |= w << (*len % W);
+        if *len % W + w_len > W {
+            buf.push(w >> (W - *len % W));
+        }
+    }
+    *len += w_len;
+}
+
+fn get_bits(buf: &[u64], start: usize, w_len: usize) -> u64 {
+    if w_len == 0 {
+        return 0;
+    }
+    let (j, o) = (start / W, start % W);
+    let mut res = buf[j] >> o;
+    if o + w_len > W {
+        res |= buf[j + 1] << (W - o);
+    }
+    res & !(!0 << w_len)
+}
+
+impl RrrDict {
+    pub fn new(a: &[bool]) -> Self {
+        let len = a.len();
+        let mut classes = vec![];
+        let mut offsets = vec![];
+        let mut classes_len = 0;
+        let mut offsets_len = 0;
+        let mut super_ones = vec![];
+        let mut super_ptr = vec![];
+        let mut ones = 0;
+        for (b, chunk) in a.chunks(BLOCK_LEN).enumerate() {
+            if b % SUPER_BLOCKS == 0 {
+                super_ones.push(ones);
+                super_ptr.push(offsets_len);
+            }
+            let w = chunk
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (i, &bit)| acc | (bit as u16) << i);
+            let class = w.count_ones() as usize;
+            push_bits(&mut classes, &mut classes_len, class as _, CLASS_LEN);
+            push_bits(
+                &mut offsets,
+                &mut offsets_len,
+                encode(w) as _,
+                OFFSET_LEN[class],
+            );
+            ones += class;
+        }
+        super_ones.push(ones);
+        Self { len, classes, offsets, super_ones, super_ptr }
+    }
+
+    pub fn len(&self) -> usize { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    pub fn get(&self, i: usize) -> bool {
+        self.check_index(i);
+        let (_, w) = self.block(i / BLOCK_LEN);
+        w >> (i % BLOCK_LEN) & 1 != 0
+    }
+
+    pub fn rank1(&self, i: usize) -> usize {
+        self.check_index(i);
+        let (ones, w) = self.block(i / BLOCK_LEN);
+        let mask = !(!0 << (i % BLOCK_LEN + 1));
+        ones + (w & mask).count_ones() as usize
+    }
+    pub fn rank0(&self, i: usize) -> usize { i + 1 - self.rank1(i) }
+
+    pub fn select1(&self, i: usize) -> usize { self.select::<true>(i) }
+    pub fn select0(&self, i: usize) -> usize { self.select::<false>(i) }
+
+    fn class(&self, b: usize) -> usize {
+        get_bits(&self.classes, b * CLASS_LEN, CLASS_LEN) as _
+    }
+
+    // (the number of ones before the block, the block)
+    fn block(&self, b: usize) -> (usize, u16) {
+        let sb = b / SUPER_BLOCKS;
+        let mut ones = self.super_ones[sb];
+        let mut ptr = self.super_ptr[sb];
+        for bi in sb * SUPER_BLOCKS..b {
+            let class = self.class(bi);
+            ones += class;
+            ptr += OFFSET_LEN[class];
+        }
+        let class = self.class(b);
+        let offset = get_bits(&self.offsets, ptr, OFFSET_LEN[class]);
+        (ones, decode(class, offset as _))
+    }
+
+    fn select<const X: bool>(&self, i: usize) -> usize {
+        let count = |sb: usize| {
+            let ones = self.super_ones[sb];
+            if X {
+                ones
+            } else {
+                sb * SUPER_BLOCKS * BLOCK_LEN - ones
+            }
+        };
+        let ones = self.super_ones[self.super_ptr.len()];
+        let total = if X { ones } else { self.len - ones };
+        if i >= total {
+            let x = X as u8;
+            panic!(
+                "the number of {x}s (is {total}) should be > index (is {i})"
+            );
+        }
+
+        let (mut lo, mut hi) = (0, self.super_ptr.len());
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            *(if count(mid) <= i { &mut lo } else { &mut hi }) = mid;
+        }
+        let sb = lo;
+        let mut i = i - count(sb);
+        let mut ptr = self.super_ptr[sb];
+        for b in sb * SUPER_BLOCKS.. {
+            let class = self.class(b);
+            let c = if X { class } else { BLOCK_LEN - class };
+            if i < c {
+                let offset = get_bits(&self.offsets, ptr, OFFSET_LEN[class]);
+                let w = decode(class, offset as _);
+                let w = if X { w } else { !w };
+                return b * BLOCK_LEN + select_in_word(w as u64, i);
+            }
+            i -= c;
+            ptr += OFFSET_LEN[class];
+        }
+        unreachable!();
+    }
+
+    fn check_index(&self, i: usize) {
+        let len = self.len;
+        if i >= len {
+            panic!(
+                "index out of bounds: the len is {len} but the index is {i}"
+            );
+        }
+    }
+}
+
+impl RankSelect for RrrDict {
+    fn len(&self) -> usize { self.len }
+    fn rank1(&self, i: usize) -> usize { self.rank1(i) }
+    fn rank0(&self, i: usize) -> usize { self.rank0(i) }
+    fn select1(&self, i: usize) -> usize { self.select1(i) }
+    fn select0(&self, i: usize) -> usize { self.select0(i) }
+}
+
+impl From<&[bool]> for RrrDict {
+    fn from(a: &[bool]) -> Self { Self::new(a) }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    #[test]
+    fn encoding() {
+        for w in 0_u16..1 << BLOCK_LEN {
+            let class = w.count_ones() as usize;
+            let offset = encode(w);
+            assert!(offset < BINOM[BLOCK_LEN][class]);
+            assert_eq!(decode(class, offset), w);
+        }
+    }
+
+    #[test]
+    fn sanity_check() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut rand = || rng.gen_range(0..100);
+        for (len, p) in [(0, 50), (1, 100), (14, 50), (3000, 1), (3000, 50)] {
+            let a: Vec<_> = (0..len).map(|_| rand() < p).collect();
+            let rs = RrrDict::new(&a);
+            assert_eq!(rs.len(), len);
+            let mut count = 0;
+            for (i, &ai) in a.iter().enumerate() {
+                count += ai as usize;
+                assert_eq!(rs.get(i), ai);
+                assert_eq!(rs.rank1(i), count);
+                assert_eq!(rs.rank0(i), i + 1 - count);
+            }
+            let ones = (0..len).filter(|&i| a[i]);
+            for (i, j) in ones.enumerate() {
+                assert_eq!(rs.select1(i), j);
+            }
+            let zeros = (0..len).filter(|&i| !a[i]);
+            for (i, j) in zeros.enumerate() {
+                assert_eq!(rs.select0(i), j);
+            }
+        }
+    }
+}
@@ -84,13 +84,9 @@ fn bench_selects(c: &mut Criterion) {
     };
 
     // assert!((0..a.len()).map(|i| rs.rank0(i)).eq(expected_rank0()));
-    // assert!((0..a.len()).map(|i| rs_nlc.rank0(i)).eq(expected_rank0()));
-    // assert!((0..a.len()).map(|i| rs_nll.rank0(i)).eq(expected_rank0()));
-    // assert!(
-    //     (0..a.len() - 1)
-    //         .map(|i| rs_rt.rank0(i + 1))
-    //         .eq(expected_rank0().take(a.len() - 1))
-    // );
+    assert!((0..a.len()).map(|i| rs_nlc.rank0(i)).eq(expected_rank0()));
+    assert!((0..a.len()).map(|i| rs_nll.rank0(i)).eq(expected_rank0()));
+    assert!((0..a.len()).map(|i| rs_rt.rank0(i)).eq(expected_rank0()));
     // assert!(
     //     (0..a.len() - 1)
     //         .map(|i| rs_t.rank0(i + 1))
@@ -98,13 +94,9 @@ fn bench_selects(c: &mut Criterion) {
     // );
 
     // assert!((0..a.len()).map(|i| rs.rank1(i)).eq(expected_rank1()));
-    // assert!((0..a.len()).map(|i| rs_nlc.rank1(i)).eq(expected_rank1()));
-    // assert!((0..a.len()).map(|i| rs_nll.rank1(i)).eq(expected_rank1()));
-    // assert!(
-    //     (0..a.len() - 1)
-    //         .map(|i| rs_rt.rank1(i + 1))
-    //         .eq(expected_rank1().take(a.len() - 1))
-    // );
+    assert!((0..a.len()).map(|i| rs_nlc.rank1(i)).eq(expected_rank1()));
+    assert!((0..a.len()).map(|i| rs_nll.rank1(i)).eq(expected_rank1()));
+    assert!((0..a.len()).map(|i| rs_rt.rank1(i)).eq(expected_rank1()));
     // assert!(
     //     (0..a.len() - 1)
     //         .map(|i| rs_t.rank1(i + 1))
@@ -173,12 +165,12 @@ fn bench_selects(c: &mut Criterion) {
 
     // bench_fn! { group, rs_rt, rank0, "runtime", "rank0-seq", 0..a.len() }
     // bench_fn! { group, rs_rt, rank1, "runtime", "rank1-seq", 0..a.len() }
-    // bench_fn! { group, rs_rt, rank0, "runtime", "rank0-rand", rank_query.iter().copied() }
-    // bench_fn! { group, rs_rt, rank1, "runtime", "rank1-rand", rank_query.iter().copied() }
+    bench_fn! { group, rs_rt, rank0, "runtime", "rank0-rand", rank_query.iter().copied() }
+    bench_fn! { group, rs_rt, rank1, "runtime", "rank1-rand", rank_query.iter().copied() }
     // bench_fn! { group, rs_rt, select0, "runtime", "select0-seq", 0..count0 }
     // bench_fn! { group, rs_rt, select1, "runtime", "select1-seq", 0..count1 }
-    // bench_fn! { group, rs_rt, select0, "runtime", "select0-rand", select0_query.iter().copied() }
-    // bench_fn! { group, rs_rt, select1, "runtime", "select1-rand", select1_query.iter().copied() }
+    bench_fn! { group, rs_rt, select0, "runtime", "select0-rand", select0_query.iter().copied() }
+    bench_fn! { group, rs_rt, select1, "runtime", "select1-rand", select1_query.iter().copied() }
 
     // bench_fn! { group, rs_t, rank0, "tree", "rank0-seq", 0..a.len() }
     // bench_fn! { group, rs_t, rank1, "tree", "rank1-seq", 0..a.len() }
@@ -192,5 +184,60 @@ fn bench_selects(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_selects);
+fn bench_runtime(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rs01dict-runtime");
+
+    let mut rng = ChaCha20Rng::from_seed([
+        0x55, 0xEF, 0xE0, 0x3C, 0x71, 0xDA, 0xFC, 0xAB, 0x5C, 0x1A, 0x9F, 0xEB,
+        0xA4, 0x9E, 0x61, 0xE6, 0x1E, 0x7E, 0x29, 0x77, 0x38, 0x9A, 0xF5, 0x67,
+        0xF5, 0xDD, 0x07, 0x06, 0xAE, 0xE4, 0x5A, 0xDC,
+    ]);
+    let query_len = 1 << 16;
+
+    for len in [1 << 16, 1 << 20, 1 << 24] {
+        for p in [5.0e-1, 1.0e-3] {
+            let dist = Bernoulli::new(p).unwrap();
+            let a: Vec<_> = (0..len).map(|_| dist.sample(&mut rng)).collect();
+            let rs = Rs01Dict::new(&a);
+            let rs_rt = Rs01DictRuntime::new(&a);
+
+            let count1 = a.iter().filter(|&&x| x).count();
+            let count0 = len - count1;
+            let rank_query: Vec<_> =
+                (0..query_len).map(|_| rng.gen_range(0..len)).collect();
+            let select0_query: Vec<_> =
+                (0..query_len).map(|_| rng.gen_range(0..count0)).collect();
+            let select1_query: Vec<_> =
+                (0..query_len).map(|_| rng.gen_range(0..count1)).collect();
+
+            let param = format!("{len}/{p:e}");
+            macro_rules! bench_fn {
+                ($name:ident, $fn:ident, $fst:literal, $query:ident) => {
+                    let id = BenchmarkId::new(
+                        concat!($fst, "/", stringify!($fn)),
+                        &param,
+                    );
+                    group.bench_function(id, |b| {
+                        b.iter(|| {
+                            for &i in &$query {
+                                black_box($name.$fn(i));
+                            }
+                        })
+                    });
+                };
+            }
+
+            bench_fn! { rs, rank1, "succinct", rank_query }
+            bench_fn! { rs, select0, "succinct", select0_query }
+            bench_fn! { rs, select1, "succinct", select1_query }
+            bench_fn! { rs_rt, rank1, "runtime", rank_query }
+            bench_fn! { rs_rt, select0, "runtime", select0_query }
+            bench_fn! { rs_rt, select1, "runtime", select1_query }
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_selects, bench_runtime);
 criterion_main!(benches);
@@ -1,5 +1,8 @@
 use std::ops::{Range, RangeBounds, RangeInclusive};
 
+use binary_io::{BinaryIo, BinaryIoError, Decoder, Encoder};
+use rank_select::RankSelect;
+use small_rank_select::{rank_bulk, select_in_word};
 use usize_bounds::UsizeBounds;
 
 const W: usize = u64::BITS as usize;
@@ -10,7 +13,7 @@ const RANK_BIT_PATTERNS: usize = 1 << RANK_SMALL_LEN;
 
 const SELECT_SMALL_LEN: usize = 15; // (1/2) log(n)/2
 const SELECT_LARGE_SPARSE_LEN: usize = 12946;
-const SELECT_LARGE_POPCNT: usize = 17;
+const SELECT_LARGE_POPCNT: usize = 15;
 const SELECT_LARGE_NODE_LEN: usize = 4;
 const SELECT_LARGE_BRANCH: usize = 4;
 const SELECT_WORD_BIT_PATTERNS: usize = 1 << SELECT_SMALL_LEN;
@@ -20,10 +23,10 @@ const SELECT_TREE_BIT_PATTERNS: usize =
 const _ASSERTION: () = {
     let popcnt = SELECT_LARGE_POPCNT;
     let node_len = SELECT_LARGE_NODE_LEN;
-    let branch = SELECT_LARGE_BRANCH;
 
+    // every node other than the root must hold its popcount
     let node_popcnt = !(!0 << node_len);
-    if node_popcnt * branch < popcnt {
+    if node_popcnt < popcnt {
         panic!();
     }
 };
@@ -137,7 +140,6 @@ trait SelectLookup<
 >
 {
     const TREE: [[(u8, u8); POPCNT]; TREE_BIT_PATTERNS];
-    const WORD: [[u8; SMALL_LEN]; WORD_BIT_PATTERNS];
 }
 
 const fn rank_lookup<const SMALL_LEN: usize, const BIT_PATTERNS: usize>()
@@ -185,27 +187,6 @@ const fn select_tree_lookup<
     table
 }
 
-const fn select_word_lookup<
-    const SMALL_LEN: usize,
-    const BIT_PATTERNS: usize,
->() -> [[u8; SMALL_LEN]; BIT_PATTERNS] {
-    let mut table = [[0; SMALL_LEN]; BIT_PATTERNS];
-    let mut i = 0;
-    while i < BIT_PATTERNS {
-        let mut j = 0;
-        let mut count = 0;
-        while j < SMALL_LEN {
-            if i >> j & 1 != 0 {
-                table[i][count] = j as _;
-                count += 1;
-            }
-            j += 1;
-        }
-        i += 1;
-    }
-    table
-}
-
 impl<
     const RANK_LARGE_LEN: usize,
     const RANK_SMALL_LEN: usize,
@@ -259,6 +240,16 @@ impl<
         Self { buf, rank_index, select1_index, select0_index }
     }
 
+    pub fn get(&self, i: usize) -> bool {
+        let len = self.buf.len;
+        if i >= len {
+            panic!(
+                "index out of bounds: the len is {len} but the index is {i}"
+            );
+        }
+        self.buf.get_single(i)
+    }
+
     pub fn rank1(&self, i: usize) -> usize {
         self.rank_index.rank(i, &self.buf)
     }
@@ -294,6 +285,66 @@ impl<
     }
 }
 
+impl<
+    const RANK_LARGE_LEN: usize,
+    const RANK_SMALL_LEN: usize,
+    const RANK_BIT_PATTERNS: usize,
+    const SELECT_SMALL_LEN: usize,
+    const SELECT_LARGE_SPARSE_LEN: usize,
+    const SELECT_LARGE_POPCNT: usize,
+    const SELECT_LARGE_NODE_LEN: usize,
+    const SELECT_LARGE_BRANCH: usize,
+    const SELECT_WORD_BIT_PATTERNS: usize,
+    const SELECT_TREE_BIT_PATTERNS: usize,
+> RankSelect
+    for Rs01DictGenerics<
+        RANK_LARGE_LEN,
+        RANK_SMALL_LEN,
+        RANK_BIT_PATTERNS,
+        SELECT_SMALL_LEN,
+        SELECT_LARGE_SPARSE_LEN,
+        SELECT_LARGE_POPCNT,
+        SELECT_LARGE_NODE_LEN,
+        SELECT_LARGE_BRANCH,
+        SELECT_WORD_BIT_PATTERNS,
+        SELECT_TREE_BIT_PATTERNS,
+    >
+{
+    fn len(&self) -> usize { self.buf.len }
+    fn rank1(&self, i: usize) -> usize { self.rank1(i) }
+    fn rank0(&self, i: usize) -> usize { self.rank0(i) }
+    fn select1(&self, i: usize) -> usize { self.select1(i) }
+    fn select0(&self, i: usize) -> usize { self.select0(i) }
+}
+
+impl<
+    const RANK_LARGE_LEN: usize,
+    const RANK_SMALL_LEN: usize,
+    const RANK_BIT_PATTERNS: usize,
+    const SELECT_SMALL_LEN: usize,
+    const SELECT_LARGE_SPARSE_LEN: usize,
+    const SELECT_LARGE_POPCNT: usize,
+    const SELECT_LARGE_NODE_LEN: usize,
+    const SELECT_LARGE_BRANCH: usize,
+    const SELECT_WORD_BIT_PATTERNS: usize,
+    const SELECT_TREE_BIT_PATTERNS: usize,
+> From<&[bool]>
+    for Rs01DictGenerics<
+        RANK_LARGE_LEN,
+        RANK_SMALL_LEN,
+        RANK_BIT_PATTERNS,
+        SELECT_SMALL_LEN,
+        SELECT_LARGE_SPARSE_LEN,
+        SELECT_LARGE_POPCNT,
+        SELECT_LARGE_NODE_LEN,
+        SELECT_LARGE_BRANCH,
+        SELECT_WORD_BIT_PATTERNS,
+        SELECT_TREE_BIT_PATTERNS,
+    >
+{
+    fn from(a: &[bool]) -> Self { Self::new(a) }
+}
+
 impl From<(Vec<u64>, usize)> for SimpleBitVec {
     fn from((buf, len): (Vec<u64>, usize)) -> Self { Self { buf, len } }
 }
@@ -398,21 +449,13 @@ impl<const LARGE_LEN: usize, const SMALL_LEN: usize, const BIT_PATTERNS: usize>
     fn new(a: &SimpleBitVec) -> Self {
         let mut small = vec![];
         let mut large = vec![];
-        let mut small_acc = 0;
-        let mut large_acc = 0;
+        let pos: Vec<_> = (0..a.len()).step_by(SMALL_LEN).collect();
         let per = LARGE_LEN / SMALL_LEN;
-        for (c, i) in a
-            .chunks::<true>(SMALL_LEN)
-            .map(|ai| Self::WORD[ai as usize][SMALL_LEN - 1] as u16)
-            .zip((0..per).cycle())
-        {
-            small.push(small_acc);
-            small_acc = if i < per - 1 { small_acc + c } else { 0 };
-
-            if i == 0 {
-                large.push(large_acc);
+        for (i, r) in rank_bulk(&a.buf, &pos).into_iter().enumerate() {
+            if i % per == 0 {
+                large.push(r as u32);
             }
-            large_acc += c as u32;
+            small.push((r - large[i / per] as usize) as u16);
         }
 
         Self { large, small }
@@ -505,8 +548,6 @@ impl<
             LARGE_BRANCH,
             TREE_BIT_PATTERNS,
         >();
-    const WORD: [[u8; SMALL_LEN]; WORD_BIT_PATTERNS] =
-        select_word_lookup::<SMALL_LEN, WORD_BIT_PATTERNS>();
 }
 
 impl<
@@ -627,14 +668,175 @@ impl<
                 let il = start + nth_word * SMALL_LEN;
                 let ir = b.len().min(il + SMALL_LEN);
                 let w = b.get::<X>(il..ir);
-                start
-                    + nth_word * SMALL_LEN
-                    + Self::WORD[w as usize][i] as usize
+                start + nth_word * SMALL_LEN + select_in_word(w, i)
+            }
+        }
+    }
+}
+
+impl BinaryIo for SimpleBitVec {
+    fn tag() -> String { "SimpleBitVec".to_owned() }
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put(&self.len);
+        enc.put(&self.buf);
+    }
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, BinaryIoError> {
+        let len: usize = dec.get()?;
+        let buf: Vec<u64> = dec.get()?;
+        if buf.len() != len.div_ceil(W) {
+            return Err(BinaryIoError::Invalid("bit vector length"));
+        }
+        Ok(Self { buf, len })
+    }
+}
+
+// encoded as `[sparse: u8] ([positions] | [tree] [start])`
+fn encode_select_inner<
+    const SMALL_LEN: usize,
+    const LARGE_SPARSE_LEN: usize,
+    const LARGE_POPCNT: usize,
+    const LARGE_NODE_LEN: usize,
+    const LARGE_BRANCH: usize,
+    const WORD_BIT_PATTERNS: usize,
+    const TREE_BIT_PATTERNS: usize,
+>(
+    index: &SelectIndex<
+        SMALL_LEN,
+        LARGE_SPARSE_LEN,
+        LARGE_POPCNT,
+        LARGE_NODE_LEN,
+        LARGE_BRANCH,
+        WORD_BIT_PATTERNS,
+        TREE_BIT_PATTERNS,
+    >,
+    enc: &mut Encoder,
+) {
+    enc.put(&index.inner.len());
+    for inner in &index.inner {
+        match inner {
+            SelectIndexInner::Sparse(pos) => {
+                enc.put(&true);
+                enc.put(pos);
+            }
+            SelectIndexInner::Dense(tree, start) => {
+                enc.put(&false);
+                enc.put(tree);
+                enc.put(start);
             }
         }
     }
 }
 
+fn decode_select_inner<
+    const SMALL_LEN: usize,
+    const LARGE_SPARSE_LEN: usize,
+    const LARGE_POPCNT: usize,
+    const LARGE_NODE_LEN: usize,
+    const LARGE_BRANCH: usize,
+    const WORD_BIT_PATTERNS: usize,
+    const TREE_BIT_PATTERNS: usize,
+>(
+    dec: &mut Decoder<'_>,
+    len: usize,
+) -> Result<
+    SelectIndex<
+        SMALL_LEN,
+        LARGE_SPARSE_LEN,
+        LARGE_POPCNT,
+        LARGE_NODE_LEN,
+        LARGE_BRANCH,
+        WORD_BIT_PATTERNS,
+        TREE_BIT_PATTERNS,
+    >,
+    BinaryIoError,
+> {
+    let count: usize = dec.get()?;
+    let mut inner = vec![];
+    for _ in 0..count {
+        inner.push(if dec.get()? {
+            let pos: Vec<usize> = dec.get()?;
+            if pos.iter().any(|&i| i >= len) {
+                return Err(BinaryIoError::Invalid("select index"));
+            }
+            SelectIndexInner::Sparse(pos)
+        } else {
+            let tree = dec.get()?;
+            let start = dec.get()?;
+            if start >= len {
+                return Err(BinaryIoError::Invalid("select index"));
+            }
+            SelectIndexInner::Dense(tree, start)
+        });
+    }
+    Ok(SelectIndex { inner })
+}
+
+/// `tag` にはパラメータも含まれ、異なるパラメータで書き出したものは
+/// 読み込めない。
+impl<
+    const RANK_LARGE_LEN: usize,
+    const RANK_SMALL_LEN: usize,
+    const RANK_BIT_PATTERNS: usize,
+    const SELECT_SMALL_LEN: usize,
+    const SELECT_LARGE_SPARSE_LEN: usize,
+    const SELECT_LARGE_POPCNT: usize,
+    const SELECT_LARGE_NODE_LEN: usize,
+    const SELECT_LARGE_BRANCH: usize,
+    const SELECT_WORD_BIT_PATTERNS: usize,
+    const SELECT_TREE_BIT_PATTERNS: usize,
+> BinaryIo
+    for Rs01DictGenerics<
+        RANK_LARGE_LEN,
+        RANK_SMALL_LEN,
+        RANK_BIT_PATTERNS,
+        SELECT_SMALL_LEN,
+        SELECT_LARGE_SPARSE_LEN,
+        SELECT_LARGE_POPCNT,
+        SELECT_LARGE_NODE_LEN,
+        SELECT_LARGE_BRANCH,
+        SELECT_WORD_BIT_PATTERNS,
+        SELECT_TREE_BIT_PATTERNS,
+    >
+{
+    fn tag() -> String {
+        let params = [
+            RANK_LARGE_LEN,
+            RANK_SMALL_LEN,
+            RANK_BIT_PATTERNS,
+            SELECT_SMALL_LEN,
+            SELECT_LARGE_SPARSE_LEN,
+            SELECT_LARGE_POPCNT,
+            SELECT_LARGE_NODE_LEN,
+            SELECT_LARGE_BRANCH,
+            SELECT_WORD_BIT_PATTERNS,
+            SELECT_TREE_BIT_PATTERNS,
+        ];
+        let params: Vec<_> = params.iter().map(|p| p.to_string()).collect();
+        format!("Rs01Dict<{}>", params.join(","))
+    }
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put(&self.buf);
+        enc.put(&self.rank_index.large);
+        enc.put(&self.rank_index.small);
+        encode_select_inner(&self.select1_index, enc);
+        encode_select_inner(&self.select0_index, enc);
+    }
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, BinaryIoError> {
+        let buf: SimpleBitVec = dec.get()?;
+        let large: Vec<u32> = dec.get()?;
+        let small: Vec<u16> = dec.get()?;
+        let small_len = buf.len.div_ceil(RANK_SMALL_LEN);
+        let large_len = small_len.div_ceil(RANK_LARGE_LEN / RANK_SMALL_LEN);
+        if small.len() != small_len || large.len() != large_len {
+            return Err(BinaryIoError::Invalid("rank index"));
+        }
+        let rank_index = RankIndex { large, small };
+        let select1_index = decode_select_inner(dec, buf.len)?;
+        let select0_index = decode_select_inner(dec, buf.len)?;
+        Ok(Self { buf, rank_index, select1_index, select0_index })
+    }
+}
+
 #[cfg(test)]
 macro_rules! bitvec {
     ($lit:literal) => {
@@ -647,6 +849,12 @@ macro_rules! bitvec {
 
 #[cfg(test)]
 mod tests {
+    use rand::{
+        distributions::{Bernoulli, Distribution},
+        SeedableRng,
+    };
+    use rand_chacha::ChaCha20Rng;
+
     use crate::*;
 
     #[test]
@@ -674,19 +882,6 @@ mod tests {
         assert_eq!(tmp.map(|x| x.1), [0, 0, 0, 3, 3, 3, 3, 7, 7]);
     }
 
-    #[test]
-    fn test_select_word_lookup() {
-        let table = select_word_lookup::<3, 8>();
-
-        assert_eq!(&table[0b001][..1], [0]);
-        assert_eq!(&table[0b010][..1], [1]);
-        assert_eq!(&table[0b011][..2], [0, 1]);
-        assert_eq!(&table[0b100][..1], [2]);
-        assert_eq!(&table[0b101][..2], [0, 2]);
-        assert_eq!(&table[0b110][..2], [1, 2]);
-        assert_eq!(&table[0b111][..3], [0, 1, 2]);
-    }
-
     #[test]
     fn test_select_index() {
         let a = bitvec!(b"110 001 001 000 010 010");
@@ -715,4 +910,49 @@ mod tests {
             assert_eq!(rs.select0(i), i);
         }
     }
+
+    #[test]
+    fn test_large() {
+        // a dense run of ones used to overflow the popcount of a node in
+        // the select tree.
+        let n = 1 << 15;
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        for p in [0.5, 0.75] {
+            let dist = Bernoulli::new(p).unwrap();
+            let a: Vec<_> = (0..n).map(|_| dist.sample(&mut rng)).collect();
+            let rs = Rs01Dict::new(&a);
+            let ones = (0..n).filter(|&i| a[i]);
+            assert!(ones.enumerate().all(|(i, j)| rs.select1(i) == j));
+            let zeros = (0..n).filter(|&i| !a[i]);
+            assert!(zeros.enumerate().all(|(i, j)| rs.select0(i) == j));
+        }
+    }
+
+    type Rs01DictSmall =
+        Rs01DictGenerics<64, 8, 256, 8, 1000, 12, 4, 3, 256, 4096>;
+
+    #[test]
+    fn test_binary_io() {
+        let a: Vec<_> =
+            (0..5000).map(|i| i % 3 == 0 || i / 1000 == 2).collect();
+        let rs = Rs01DictSmall::new(&a);
+        let mut buf = vec![];
+        rs.write_to(&mut buf).unwrap();
+
+        let rs = Rs01DictSmall::read_from(&mut buf.as_slice()).unwrap();
+        let mut count = 0;
+        for (i, &ai) in a.iter().enumerate() {
+            count += ai as usize;
+            assert_eq!(rs.rank1(i), count);
+        }
+        let ones = (0..a.len()).filter(|&i| a[i]);
+        assert!(ones.enumerate().all(|(i, j)| rs.select1(i) == j));
+        let zeros = (0..a.len()).filter(|&i| !a[i]);
+        assert!(zeros.enumerate().all(|(i, j)| rs.select0(i) == j));
+
+        let res = Rs01Dict::read_from(&mut buf.as_slice());
+        assert!(matches!(res, Err(BinaryIoError::Tag { .. })));
+        let res = Rs01DictSmall::read_from(&mut &buf[..buf.len() - 1]);
+        assert!(matches!(res, Err(BinaryIoError::Truncated)));
+    }
 }
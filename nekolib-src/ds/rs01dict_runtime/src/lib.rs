@@ -1,6 +1,7 @@
-#![allow(unused)]
+use std::ops::{Range, RangeBounds};
 
-use std::ops::Range;
+use rank_select::RankSelect;
+use usize_bounds::UsizeBounds;
 
 const W: usize = u64::BITS as usize;
 
@@ -10,16 +11,29 @@ struct IntVec {
     len: usize,
 }
 
+/// rank/select 辞書。
+///
+/// `Rs01Dict` と同様だが、ブロックの大きさなどを構築時に $n$ から決める。
+/// $\lambda = \lceil\log_2(n+1)\rceil$ として、表引きの単位を
+/// $\lambda/4$ bits、rank の大ブロックを $\lambda^2/4$ bits とする。
 pub struct Rs01DictRuntime {
     buf: IntVec,
+    ones: usize,
+    tables: Tables,
     rank_index: RankIndex,
     select_index: (SelectIndex, SelectIndex),
 }
 
+// lookup tables for words of `word_len` bits, shared by rank and select.
+struct Tables {
+    word_len: usize,
+    rank: IntVec,
+    select: IntVec,
+}
+
 struct RankIndex {
     large: IntVec,
     small: IntVec,
-    table: IntVec,
     large_len: usize,
     small_len: usize,
 }
@@ -30,17 +44,16 @@ struct SelectIndex {
     small_indir: IntVec,
     small_sparse: IntVec,
     small_sparse_offset: IntVec,
-    small_dense_max: usize,
     large_popcnt: usize,
     large_start: IntVec,
     large_indir: IntVec,
     large_sparse: IntVec,
-    table: IntVec,
 }
 
 impl IntVec {
     pub fn new(unit: usize) -> Self { Self { unit, buf: vec![], len: 0 } }
     pub fn len(&self) -> usize { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
     pub fn bitlen(&self) -> usize { self.len * self.unit }
 
     pub fn push(&mut self, w: u64) {
@@ -50,7 +63,7 @@ impl IntVec {
         let bitlen = self.bitlen();
         if unit == 0 {
             // nothing to do
-        } else if bitlen % W == 0 {
+        } else if bitlen.is_multiple_of(W) {
             self.buf.push(w);
         } else {
             self.buf[bitlen / W] |= w << (bitlen % W);
@@ -77,7 +90,7 @@ impl IntVec {
         let mask = if end - start == W { !0 } else { !(!0 << (end - start)) };
         let res = if start == end {
             0
-        } else if start % W == 0 {
+        } else if start.is_multiple_of(W) {
             self.buf[start / W]
         } else if end <= (start / W + 1) * W {
             self.buf[start / W] >> (start % W)
@@ -105,14 +118,67 @@ fn lg_half(n: usize) -> usize {
     // log(n)/2
     (1_usize..).find(|&i| 4_usize.saturating_pow(i as _) >= n).unwrap()
 }
+fn lg_quarter(n: usize) -> usize {
+    // (1/2) log(n)/2
+    1.max(bitlen(n) / 4)
+}
+
+impl Tables {
+    fn new(len: usize) -> Self {
+        let word_len = lg_quarter(len);
+        let rank = Self::rank_table(word_len);
+        let select = Self::select_table(word_len);
+        Self { word_len, rank, select }
+    }
+
+    fn rank_table(len: usize) -> IntVec {
+        let unit = bitlen(len);
+        let mut table = IntVec::new(unit);
+        for i in 0..1 << len {
+            let mut cur = 0;
+            for j in 0..len {
+                table.push(cur);
+                if i >> j & 1 != 0 {
+                    cur += 1;
+                }
+            }
+        }
+        table
+    }
+
+    fn select_table(len: usize) -> IntVec {
+        let unit = bitlen(len);
+        let mut table = IntVec::new(unit);
+        for i in 0..1 << len {
+            let mut cur = 0;
+            for j in 0..len {
+                if i >> j & 1 != 0 {
+                    table.push(j as _);
+                    cur += 1;
+                }
+            }
+            for _ in cur..len {
+                table.push(0);
+            }
+        }
+        table
+    }
+
+    // the number of ones in `w[..i]`
+    fn rank(&self, w: u64, i: usize) -> usize {
+        self.rank.get_usize(w as usize * self.word_len + i)
+    }
+    // the position of the `i`-th one in `w`
+    fn select(&self, w: u64, i: usize) -> usize {
+        self.select.get_usize(w as usize * self.word_len + i)
+    }
+}
 
 impl RankIndex {
-    pub fn new(buf: &[bool]) -> Self {
+    pub fn new(buf: &[bool], tables: &Tables) -> Self {
         let len = buf.len();
-        let small_len = (1_usize..)
-            .find(|&i| 4_usize.saturating_pow(i as _) >= len)
-            .unwrap(); // log(n)/2
-        let large_len = (2 * small_len).pow(2); // log(n)^2
+        let small_len = tables.word_len; // (1/2) log(n)/2
+        let large_len = small_len * 1.max(bitlen(len).pow(2) / 4 / small_len); // (1/4) log(n)^2
 
         let small_bitlen = bitlen(len.min(large_len));
         let large_bitlen = bitlen(len);
@@ -133,65 +199,35 @@ impl RankIndex {
             if i == 0 {
                 large.push(large_acc);
             }
-            large_acc += c as u64;
-        }
-
-        let table = Self::table(small_len);
-        Self { large, small, table, large_len, small_len }
-    }
-
-    fn table(len: usize) -> IntVec {
-        let unit = bitlen(len);
-        let mut table = IntVec::new(unit);
-        for i in 0..1 << len {
-            let mut cur = 0;
-            for j in 0..len {
-                table.push(cur);
-                if i >> j & 1 != 0 {
-                    cur += 1;
-                }
-            }
+            large_acc += c;
         }
-        table
-    }
 
-    fn lookup(&self, w: u64, i: usize) -> usize {
-        let wi = w as usize * self.small_len + i;
-        self.table.get_usize(wi)
+        Self { large, small, large_len, small_len }
     }
 
-    pub fn rank1(&self, i: usize, b: &IntVec) -> usize {
+    // the number of ones in `b[..=i]`
+    pub fn rank1(&self, i: usize, b: &IntVec, tables: &Tables) -> usize {
         let large_acc = self.large.get_usize(i / self.large_len);
         let small_acc = self.small.get_usize(i / self.small_len);
         let il = i / self.small_len * self.small_len;
         let ir = il + self.small_len;
         let w = b.bits_range::<true>(il..ir);
-        let small = self.lookup(w, i % self.small_len);
+        let j = i % self.small_len;
+        let small = tables.rank(w, j) + (w >> j & 1) as usize;
         large_acc + small_acc + small
     }
-    pub fn rank0(&self, i: usize, b: &IntVec) -> usize { i - self.rank1(i, b) }
-    pub fn rank<const X: bool>(&self, i: usize, b: &IntVec) -> usize {
-        if X { self.rank1(i, b) } else { self.rank0(i, b) }
-    }
-
-    #[cfg(test)]
-    pub fn size_info(&self) -> usize {
-        // eprintln!("large: {} bits", self.large.bitlen());
-        // eprintln!("small: {} bits", self.small.bitlen());
-        // eprintln!("table: {} bits", self.table.bitlen());
-
-        self.large.bitlen() + self.small.bitlen() + self.table.bitlen()
-    }
 }
 
 impl SelectIndex {
-    pub fn new<const X: bool>(buf: &[bool]) -> Self {
+    pub fn new<const X: bool>(buf: &[bool], tables: &Tables) -> Self {
         let len = buf.len();
         let small_popcnt = lg_half(len);
         let large_popcnt = (2 * small_popcnt).pow(2); // log(n)^2
-        let small_dense_max =
+        // scanned by `tables.word_len` bits
+        let small_dense_max = tables.word_len.max(
             (((len as f64).log2().max(1.0).log2().max(1.0).powi(4) / 24.0)
-                .ceil()) as usize;
+                .ceil()) as usize,
+        );
         let large_dense_max = large_popcnt.pow(2); // log(n)^4
         let mut large_start = IntVec::new(bitlen(len));
         let mut large_indir = IntVec::new(bitlen(len) + 1);
@@ -203,8 +239,8 @@ impl SelectIndex {
 
         let mut start = 0;
         let mut pos = vec![];
-        for i in 0..len {
-            if buf[i] == X {
+        for (i, &bi) in buf.iter().enumerate() {
+            if bi == X {
                 pos.push(i);
             }
             if !(pos.len() == large_popcnt || i == len - 1) {
@@ -216,13 +252,12 @@ impl SelectIndex {
             large_start.push(cur_large_start as _);
             small_sparse_offset.push(small_sparse.len() as _);
             if cur_large_end + 1 - cur_large_start > large_dense_max {
-                large_indir.push((large_sparse.len() << 1 | 0) as _);
+                large_indir.push((large_sparse.len() << 1) as _);
                 for p in pos.drain(..) {
                     large_sparse.push(p as _);
                 }
             } else {
                 large_indir.push((small_start.len() << 1 | 1) as _);
-                let small_start_offset = small_start.len();
                 let small_sparse_offset = small_sparse.len();
                 let mut cur_small_start = cur_large_start;
                 for j in (0..pos.len()).step_by(small_popcnt) {
@@ -238,13 +273,13 @@ impl SelectIndex {
                     if end + 1 - start > small_dense_max {
                         let tmp = (small_sparse.len() - small_sparse_offset)
                             / small_popcnt;
-                        small_indir.push((tmp << 1 | 0) as _);
+                        small_indir.push((tmp << 1) as _);
                         for &p in &pos[j..pos.len().min(j + small_popcnt)] {
                             let pos_offset = p - start;
                             small_sparse.push(pos_offset as _);
                         }
                     } else {
-                        small_indir.push(0 << 1 | 1);
+                        small_indir.push(1);
                     }
                     cur_small_start = end + 1;
                 }
@@ -254,46 +289,25 @@ impl SelectIndex {
             start = i + 1;
         }
 
-        let table = Self::table(small_dense_max);
         Self {
             small_popcnt,
             small_start,
             small_indir,
             small_sparse,
             small_sparse_offset,
-            small_dense_max,
             large_popcnt,
             large_start,
             large_indir,
             large_sparse,
-            table,
         }
     }
 
-    fn table(len: usize) -> IntVec {
-        let unit = bitlen(len);
-        let mut table = IntVec::new(unit);
-        for i in 0..1 << len {
-            let mut cur = 0;
-            for j in 0..len {
-                if i >> j & 1 != 0 {
-                    table.push(j as _);
-                    cur += 1;
-                }
-            }
-            for _ in cur..len {
-                table.push(0);
-            }
-        }
-        table
-    }
-
-    fn lookup(&self, w: u64, i: usize) -> usize {
-        let wi = w as usize * self.small_dense_max + i;
-        self.table.get_usize(wi)
-    }
-
-    pub fn select<const X: bool>(&self, i: usize, b: &IntVec) -> usize {
+    pub fn select<const X: bool>(
+        &self,
+        i: usize,
+        b: &IntVec,
+        tables: &Tables,
+    ) -> usize {
         let (il_div, il_mod) = (i / self.large_popcnt, i % self.large_popcnt);
         let large = self.large_indir.get_usize(il_div);
         let (large_i, large_ty) = (large >> 1, large & 1);
@@ -315,87 +329,94 @@ impl SelectIndex {
                     .get_usize(offset + small_i * self.small_popcnt + is_mod);
                 large_start + small_start + small_sparse
             } else {
-                let offset = large_start + small_start;
-                let w =
-                    b.bits_range::<X>(offset..offset + self.small_dense_max);
-                offset + self.lookup(w, is_mod)
+                let mut offset = large_start + small_start;
+                let mut i = is_mod;
+                loop {
+                    let w = b.bits_range::<X>(offset..offset + tables.word_len);
+                    let count = w.count_ones() as usize;
+                    if i < count {
+                        break offset + tables.select(w, i);
+                    }
+                    i -= count;
+                    offset += tables.word_len;
+                }
             }
         }
     }
-
-    #[cfg(test)]
-    pub fn size_info(&self) -> usize {
-        eprintln!("small_start: {} bits", self.small_start.bitlen());
-        eprintln!("small_indir: {} bits", self.small_indir.bitlen());
-        eprintln!("small_sparse: {} bits", self.small_sparse.bitlen());
-        eprintln!(
-            "small_sparse_offset: {} bits",
-            self.small_sparse_offset.bitlen()
-        );
-        eprintln!("large_start: {} bits", self.large_start.bitlen());
-        eprintln!("large_indir: {} bits", self.large_indir.bitlen());
-        eprintln!("large_sparse: {} bits", self.large_sparse.bitlen());
-        eprintln!("table: {} bits", self.table.bitlen());
-
-        self.small_start.bitlen()
-            + self.small_indir.bitlen()
-            + self.small_sparse.bitlen()
-            + self.small_sparse_offset.bitlen()
-            + self.large_start.bitlen()
-            + self.large_indir.bitlen()
-            + self.large_sparse.bitlen()
-            + self.table.bitlen()
-    }
 }
 
 impl Rs01DictRuntime {
     pub fn new(a: &[bool]) -> Self {
-        let rank_index = RankIndex::new(&a);
+        let tables = Tables::new(a.len());
+        let rank_index = RankIndex::new(a, &tables);
         let mut buf = IntVec::new(1);
         for &x in a {
             buf.push(x as _);
         }
-        let select_index =
-            (SelectIndex::new::<false>(&a), SelectIndex::new::<true>(&a));
-
-        // select.0 と select.1 で同じ lookup table を作るの無駄だから、
-        // rank も含めてそれらは親のクラスで持つ設計でもいいかも？
-        // buf と一緒に table も渡す感じで
-        Self { buf, rank_index, select_index }
+        let ones = a.iter().filter(|&&x| x).count();
+        let select_index = (
+            SelectIndex::new::<false>(a, &tables),
+            SelectIndex::new::<true>(a, &tables),
+        );
+        Self { buf, ones, tables, rank_index, select_index }
     }
 
+    pub fn len(&self) -> usize { self.buf.len() }
+    pub fn is_empty(&self) -> bool { self.buf.is_empty() }
+
     pub fn rank<const X: bool>(&self, i: usize) -> usize {
-        self.rank_index.rank::<X>(i, &self.buf)
+        let len = self.len();
+        if i >= len {
+            panic!(
+                "index out of bounds: the len is {len} but the index is {i}"
+            );
+        }
+        let rank1 = self.rank_index.rank1(i, &self.buf, &self.tables);
+        if X { rank1 } else { i + 1 - rank1 }
     }
     pub fn rank0(&self, i: usize) -> usize { self.rank::<false>(i) }
     pub fn rank1(&self, i: usize) -> usize { self.rank::<true>(i) }
 
     pub fn select<const X: bool>(&self, i: usize) -> usize {
-        if X {
-            self.select_index.1.select::<X>(i, &self.buf)
-        } else {
-            self.select_index.0.select::<X>(i, &self.buf)
+        let count = if X { self.ones } else { self.len() - self.ones };
+        if i >= count {
+            let x = X as u8;
+            panic!(
+                "the number of {x}s (is {count}) should be > index (is {i})"
+            );
         }
+        let index = if X { &self.select_index.1 } else { &self.select_index.0 };
+        index.select::<X>(i, &self.buf, &self.tables)
     }
     pub fn select0(&self, i: usize) -> usize { self.select::<false>(i) }
     pub fn select1(&self, i: usize) -> usize { self.select::<true>(i) }
 
-    #[cfg(test)]
-    pub fn size_info(&self) {
-        let mut sum = 0;
-        sum += self.rank_index.size_info();
-        sum += self.select_index.0.size_info();
-        sum += self.select_index.1.size_info();
-        let ratio = sum as f64 / self.buf.len() as f64;
-        eprintln!("total: {sum} bits (x{ratio:.03})");
+    pub fn count1(&self, range: impl RangeBounds<usize>) -> usize {
+        RankSelect::count1(self, range)
+    }
+    pub fn count0(&self, range: impl RangeBounds<usize>) -> usize {
+        let Range { start, end } = range.to_range(self.len());
+        (end - start) - self.count1(start..end)
     }
 }
 
+impl RankSelect for Rs01DictRuntime {
+    fn len(&self) -> usize { self.len() }
+    fn rank1(&self, i: usize) -> usize { self.rank1(i) }
+    fn rank0(&self, i: usize) -> usize { self.rank0(i) }
+    fn select1(&self, i: usize) -> usize { self.select1(i) }
+    fn select0(&self, i: usize) -> usize { self.select0(i) }
+}
+
+impl From<&[bool]> for Rs01DictRuntime {
+    fn from(a: &[bool]) -> Self { Self::new(a) }
+}
+
 #[cfg(test)]
 mod tests {
     use rand::{
+        SeedableRng,
         distributions::{Bernoulli, Distribution},
-        Rng, SeedableRng,
     };
     use rand_chacha::ChaCha20Rng;
 
@@ -416,17 +437,15 @@ mod tests {
         let naive: Vec<_> = a
             .iter()
             .map(|&x| x as usize)
-            .scan(0, |acc, x| Some(std::mem::replace(acc, *acc + x)))
+            .scan(0, |acc, x| {
+                *acc += x;
+                Some(*acc)
+            })
             .collect();
         let dict = Rs01DictRuntime::new(&a);
-        for i in 0..len {
-            assert_eq!(dict.rank1(i), naive[i], "i: {}", i);
-            assert_eq!(dict.rank0(i), i - naive[i], "i: {}", i);
-        }
-        if p == 1.0 {
-            eprintln!("---");
-            eprintln!("a.len(): {}", a.len());
-            dict.size_info();
+        for (i, &ni) in naive.iter().enumerate() {
+            assert_eq!(dict.rank1(i), ni, "i: {}", i);
+            assert_eq!(dict.rank0(i), i + 1 - ni, "i: {}", i);
         }
     }
 
@@ -436,16 +455,11 @@ mod tests {
         let a: Vec<_> = (0..len).map(|_| dist.sample(&mut rng)).collect();
         let naive: (Vec<_>, _) = (0..len).partition(|&i| !a[i]);
         let dict = Rs01DictRuntime::new(&a);
-        for i in 0..naive.0.len() {
-            assert_eq!(dict.select0(i), naive.0[i], "i: {}", i);
-        }
-        for i in 0..naive.1.len() {
-            assert_eq!(dict.select1(i), naive.1[i], "i: {}", i);
+        for (i, &ni) in naive.0.iter().enumerate() {
+            assert_eq!(dict.select0(i), ni, "i: {}", i);
         }
-        if p == 1.0 {
-            eprintln!("---");
-            eprintln!("a.len(): {}", a.len());
-            dict.size_info();
+        for (i, &ni) in naive.1.iter().enumerate() {
+            assert_eq!(dict.select1(i), ni, "i: {}", i);
         }
     }
 
@@ -2,13 +2,19 @@ use inner::doc_inline_reexport;
 
 doc_inline_reexport! {
     btree_seq,
+    dynamic_bit_vec,
+    elias_fano,
     fibonacci_heap,
     foldable_deque,
     foldable_queue,
     interval_map,
     interval_set,
+    li_chao_tree,
     n1_rmq,
+    rank_select,
+    rrr_dict,
     rs01_dict,
+    rs01dict_runtime,
     sqrt_bucket,
     union_find,
     vec_lazy_segtree,
@@ -2,13 +2,15 @@
 
 use std::ops::{Range, RangeBounds, RangeInclusive};
 
+use binary_io::{BinaryIo, BinaryIoError, Decoder, Encoder};
+use rank_select::RankSelect;
 use rs01_dict::Rs01Dict;
 use usize_bounds::UsizeBounds;
 
-pub struct WaveletMatrix<I> {
+pub struct WaveletMatrix<I, B = Rs01Dict> {
     len: usize,
     bitlen: usize,
-    buf: Vec<Rs01Dict>,
+    buf: Vec<B>,
     zeros: Vec<usize>,
     orig: Vec<I>,
 }
@@ -31,7 +33,15 @@ impl Count3wayResult {
 }
 
 impl<I: WmInt> From<Vec<I>> for WaveletMatrix<I> {
-    fn from(orig: Vec<I>) -> Self {
+    fn from(orig: Vec<I>) -> Self { Self::from_backend(orig) }
+}
+
+impl<I: WmInt, B> WaveletMatrix<I, B>
+where
+    B: RankSelect + for<'a> From<&'a [bool]>,
+{
+    /// 各段のビット列に `B` を用いて構築する。
+    pub fn from_backend(orig: Vec<I>) -> Self {
         let len = orig.len();
         let bitlen =
             orig.iter().map(|ai| ai.bitlen()).max().unwrap_or(0) as usize;
@@ -47,7 +57,7 @@ impl<I: WmInt> From<Vec<I>> for WaveletMatrix<I> {
                 vb[j] = aj.test(i);
             }
             zeros[i] = zero.len();
-            buf.push(Rs01Dict::new(&vb));
+            buf.push(B::from(&vb));
             whole = zero;
             whole.append(&mut one);
         }
@@ -56,7 +66,11 @@ impl<I: WmInt> From<Vec<I>> for WaveletMatrix<I> {
     }
 }
 
-impl<I: WmInt> WaveletMatrix<I> {
+impl<I: WmInt, B: RankSelect> WaveletMatrix<I, B> {
+    pub fn len(&self) -> usize { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+    pub fn get(&self, i: usize) -> I { self.orig[i] }
+
     pub fn count<R: WmIntRange<Int = I>>(
         &self,
         range: impl RangeBounds<usize>,
@@ -137,6 +151,28 @@ impl<I: WmInt> WaveletMatrix<I> {
     }
 }
 
+impl<I: BinaryIo, B: BinaryIo> BinaryIo for WaveletMatrix<I, B> {
+    fn tag() -> String { format!("WaveletMatrix<{},{}>", I::tag(), B::tag()) }
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put(&self.len);
+        enc.put(&self.bitlen);
+        enc.put(&self.buf);
+        enc.put(&self.zeros);
+        enc.put(&self.orig);
+    }
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, BinaryIoError> {
+        let len = dec.get()?;
+        let bitlen = dec.get()?;
+        let buf: Vec<B> = dec.get()?;
+        let zeros: Vec<usize> = dec.get()?;
+        let orig: Vec<I> = dec.get()?;
+        if buf.len() != bitlen || zeros.len() != bitlen || orig.len() != len {
+            return Err(BinaryIoError::Invalid("wavelet matrix"));
+        }
+        Ok(Self { len, bitlen, buf, zeros, orig })
+    }
+}
+
 pub trait WmInt: Copy + Eq {
     fn test(self, i: usize) -> bool;
     fn set(&mut self, i: usize);
@@ -172,3 +208,75 @@ macro_rules! impl_uint {
 }
 
 impl_uint! { u8 u16 u32 u64 u128 usize }
+
+#[cfg(test)]
+mod tests {
+    use bit_vector::Rs01DictNlC;
+    use elias_fano::EliasFano;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+    use rrr_dict::RrrDict;
+    use rs01_dict::Rs01DictGenerics;
+
+    use crate::*;
+
+    fn check<B>(a: &[u32])
+    where
+        B: RankSelect + for<'a> From<&'a [bool]>,
+    {
+        let wm = WaveletMatrix::<_, B>::from_backend(a.to_vec());
+        let n = a.len();
+        for l in 0..=n {
+            for r in l..=n {
+                let mut sorted = a[l..r].to_vec();
+                sorted.sort_unstable();
+                for (k, &x) in sorted.iter().enumerate() {
+                    assert_eq!(wm.quantile(l..r, k), Some(x));
+                }
+                assert_eq!(wm.quantile(l..r, r - l), None);
+                for x in 0..16 {
+                    let lt = sorted.iter().filter(|&&y| y < x).count();
+                    let eq = sorted.iter().filter(|&&y| y == x).count();
+                    let actual = wm.count_3way(l..r, x);
+                    assert_eq!((actual.lt(), actual.eq()), (lt, eq));
+                    let le = sorted.iter().filter(|&&y| y <= x + 2).count();
+                    assert_eq!(wm.count(l..r, x..=x + 2), le - lt);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn backends() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let a: Vec<_> = (0..40).map(|_| rng.gen_range(0..14_u32)).collect();
+        check::<Rs01DictNlC>(&a);
+        check::<RrrDict>(&a);
+        check::<EliasFano>(&a);
+    }
+
+    #[test]
+    fn binary_io() {
+        type Rs01DictSmall =
+            Rs01DictGenerics<64, 8, 256, 8, 1000, 12, 4, 3, 256, 4096>;
+        let a: Vec<u32> = (0..300).map(|i| i * i % 37).collect();
+        let wm = WaveletMatrix::<_, Rs01DictSmall>::from_backend(a.clone());
+        let mut buf = vec![];
+        wm.write_to(&mut buf).unwrap();
+
+        let wm = WaveletMatrix::<u32, Rs01DictSmall>::read_from(&mut &buf[..])
+            .unwrap();
+        let mut sorted = a[50..250].to_vec();
+        sorted.sort_unstable();
+        for (k, &x) in sorted.iter().enumerate() {
+            assert_eq!(wm.quantile(50..250, k), Some(x));
+        }
+        assert_eq!(
+            wm.count(.., 0..=9_u32),
+            a.iter().filter(|&&x| x <= 9).count()
+        );
+
+        let res = WaveletMatrix::<u64, Rs01DictSmall>::read_from(&mut &buf[..]);
+        assert!(matches!(res, Err(BinaryIoError::Tag { .. })));
+    }
+}
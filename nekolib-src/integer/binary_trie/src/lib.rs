@@ -0,0 +1,255 @@
+use std::ops::BitXor;
+
+pub trait TrieInt: Copy + Eq + Ord + BitXor<Output = Self> {
+    const BITS: u32;
+    fn test(self, i: u32) -> bool;
+    fn set(&mut self, i: u32);
+    fn zero() -> Self;
+}
+
+macro_rules! impl_uint {
+    ( $($ty:ty)* ) => { $(
+        impl TrieInt for $ty {
+            const BITS: u32 = <$ty>::BITS;
+            fn test(self, i: u32) -> bool { self >> i & 1 != 0 }
+            fn set(&mut self, i: u32) { *self |= 1 << i; }
+            fn zero() -> $ty { 0 }
+        }
+    )* }
+}
+
+impl_uint! { u8 u16 u32 u64 u128 usize }
+
+#[derive(Clone, Copy, Default)]
+struct Node {
+    // `0` for the absence, as the root is never a child.
+    children: [usize; 2],
+    count: usize,
+}
+
+/// 非負整数の多重集合。
+///
+/// 全体に xor をかける操作は遅延して扱う。
+#[derive(Clone)]
+pub struct BinaryTrie<I> {
+    nodes: Vec<Node>,
+    mask: I,
+}
+
+impl<I: TrieInt> BinaryTrie<I> {
+    pub fn new() -> Self {
+        Self { nodes: vec![Node::default()], mask: I::zero() }
+    }
+
+    pub fn len(&self) -> usize { self.nodes[0].count }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    pub fn insert(&mut self, x: I) {
+        let x = x ^ self.mask;
+        let mut v = 0;
+        self.nodes[v].count += 1;
+        for i in (0..I::BITS).rev() {
+            let b = x.test(i) as usize;
+            if self.nodes[v].children[b] == 0 {
+                self.nodes[v].children[b] = self.nodes.len();
+                self.nodes.push(Node::default());
+            }
+            v = self.nodes[v].children[b];
+            self.nodes[v].count += 1;
+        }
+    }
+    /// `x` を一つ取り除く。含まれていなかった場合は `false` を返す。
+    pub fn remove(&mut self, x: I) -> bool {
+        if self.count(x) == 0 {
+            return false;
+        }
+        let x = x ^ self.mask;
+        let mut v = 0;
+        self.nodes[v].count -= 1;
+        for i in (0..I::BITS).rev() {
+            v = self.nodes[v].children[x.test(i) as usize];
+            self.nodes[v].count -= 1;
+        }
+        true
+    }
+    pub fn count(&self, x: I) -> usize {
+        let x = x ^ self.mask;
+        let mut v = 0;
+        for i in (0..I::BITS).rev() {
+            v = self.child(v, x.test(i));
+            if v == 0 {
+                return 0;
+            }
+        }
+        self.nodes[v].count
+    }
+
+    /// 各要素 $y$ を $y\\oplus x$ で置き換える。
+    pub fn xor_all(&mut self, x: I) { self.mask = self.mask ^ x; }
+
+    /// $\\min_y\\,(y\\oplus x)$ を返す。
+    pub fn min_xor(&self, x: I) -> Option<I> { self.extremum_xor(x, false) }
+    /// $\\max_y\\,(y\\oplus x)$ を返す。
+    pub fn max_xor(&self, x: I) -> Option<I> { self.extremum_xor(x, true) }
+
+    /// $y\\oplus x\\lt k$ なる $y$ の個数を返す。
+    pub fn count_less_xor(&self, x: I, k: I) -> usize {
+        let x = x ^ self.mask;
+        let mut res = 0;
+        let mut v = 0;
+        for i in (0..I::BITS).rev() {
+            let b = x.test(i);
+            if k.test(i) {
+                // (y ^ x).test(i) == false
+                res += self.count_at(self.child(v, b));
+                v = self.child(v, !b);
+            } else {
+                v = self.child(v, b);
+            }
+            if v == 0 {
+                break;
+            }
+        }
+        res
+    }
+
+    /// 小さい方から `n` 番目 (0-indexed) の要素を返す。
+    pub fn nth(&self, mut n: usize) -> Option<I> {
+        if n >= self.len() {
+            return None;
+        }
+        let mut res = I::zero();
+        let mut v = 0;
+        for i in (0..I::BITS).rev() {
+            let b = self.mask.test(i);
+            let zero = self.count_at(self.child(v, b));
+            if n < zero {
+                v = self.child(v, b);
+            } else {
+                n -= zero;
+                res.set(i);
+                v = self.child(v, !b);
+            }
+        }
+        Some(res)
+    }
+
+    fn child(&self, v: usize, b: bool) -> usize {
+        self.nodes[v].children[b as usize]
+    }
+    fn count_at(&self, v: usize) -> usize {
+        if v == 0 {
+            0
+        } else {
+            self.nodes[v].count
+        }
+    }
+
+    fn extremum_xor(&self, x: I, max: bool) -> Option<I> {
+        if self.is_empty() {
+            return None;
+        }
+        let x = x ^ self.mask;
+        let mut res = I::zero();
+        let mut v = 0;
+        for i in (0..I::BITS).rev() {
+            // prefer `(y ^ x).test(i) == max`
+            let b = x.test(i) ^ max;
+            let u = self.child(v, b);
+            if self.count_at(u) > 0 {
+                v = u;
+                if max {
+                    res.set(i);
+                }
+            } else {
+                v = self.child(v, !b);
+                if !max {
+                    res.set(i);
+                }
+            }
+        }
+        Some(res)
+    }
+}
+
+impl<I: TrieInt> Default for BinaryTrie<I> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<I: TrieInt> FromIterator<I> for BinaryTrie<I> {
+    fn from_iter<It: IntoIterator<Item = I>>(iter: It) -> Self {
+        let mut res = Self::new();
+        for x in iter {
+            res.insert(x);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    #[test]
+    fn sanity_check() {
+        let mut trie = BinaryTrie::<u32>::new();
+        let mut naive: Vec<u32> = vec![];
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut rand = |m: u32| rng.gen_range(0..m);
+        for _ in 0..500 {
+            match rand(5) {
+                0 | 1 => {
+                    let x = rand(64);
+                    trie.insert(x);
+                    naive.push(x);
+                }
+                2 => {
+                    let x = rand(64);
+                    let expected = naive.iter().position(|&y| y == x);
+                    assert_eq!(trie.remove(x), expected.is_some());
+                    if let Some(i) = expected {
+                        naive.swap_remove(i);
+                    }
+                }
+                3 => {
+                    let m = rand(64);
+                    trie.xor_all(m);
+                    naive.iter_mut().for_each(|y| *y ^= m);
+                }
+                _ => {}
+            }
+
+            naive.sort_unstable();
+            assert_eq!(trie.len(), naive.len());
+            for (n, &y) in naive.iter().enumerate() {
+                assert_eq!(trie.nth(n), Some(y));
+            }
+            assert_eq!(trie.nth(naive.len()), None);
+            for x in 0..64 {
+                let count = naive.iter().filter(|&&y| y == x).count();
+                assert_eq!(trie.count(x), count);
+                let xor = naive.iter().map(|&y| y ^ x);
+                assert_eq!(trie.min_xor(x), xor.clone().min());
+                assert_eq!(trie.max_xor(x), xor.max());
+                for k in [0, 1, 5, 17, 32, 63, 64, 100] {
+                    let count = naive.iter().filter(|&&y| y ^ x < k).count();
+                    assert_eq!(trie.count_less_xor(x, k), count);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn full_width() {
+        let trie: BinaryTrie<u64> =
+            [0, 1 << 63, u64::MAX].into_iter().collect();
+        assert_eq!(trie.max_xor(0), Some(u64::MAX));
+        assert_eq!(trie.max_xor(u64::MAX), Some(u64::MAX));
+        assert_eq!(trie.min_xor(1 << 63 | 1), Some(1));
+        assert_eq!(trie.count_less_xor(0, 1 << 63), 1);
+        assert_eq!(trie.nth(1), Some(1 << 63));
+    }
+}
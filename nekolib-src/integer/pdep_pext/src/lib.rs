@@ -54,8 +54,8 @@ impl_pdep_pext_mask! { (PdepPextMaskUsize, usize, [0, 1, 2, 3], 5) }
 #[cfg(target_pointer_width = "64")]
 impl_pdep_pext_mask! { (PdepPextMaskUsize, usize, [0, 1, 2, 3, 4], 6) }
 
-macro_rules! impl_pext {
-    ( ($maskty:ident, $basety:ident, $lg:literal) ) => {
+macro_rules! impl_pext_raw {
+    ( ($basety:ident, $lg:literal) ) => {
         impl Pext<$basety> for $basety {
             fn pext(self, mut m: $basety) -> $basety {
                 let mut x = self & m;
@@ -74,6 +74,12 @@ macro_rules! impl_pext {
                 x
             }
         }
+    };
+    ( $( ( $($tt:tt)* ), )* ) => { $( impl_pext_raw!( ( $($tt)* ) ); )* };
+}
+
+macro_rules! impl_pext {
+    ( ($maskty:ident, $basety:ident, $lg:literal) ) => {
         impl Pext<$maskty> for $basety {
             fn pext(self, mask: $maskty) -> $basety {
                 let mut x = self & mask.0;
@@ -89,6 +95,23 @@ macro_rules! impl_pext {
     ( $( ( $($tt:tt)* ), )* ) => { $( impl_pext!( ( $($tt)* ) ); )* };
 }
 
+impl_pext_raw! {
+    (u8, 3),
+    (u16, 4),
+    (u128, 7),
+}
+
+#[cfg(target_pointer_width = "32")]
+impl_pext_raw! { (usize, 5) }
+#[cfg(target_pointer_width = "64")]
+impl_pext_raw! { (usize, 6) }
+
+#[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+impl_pext_raw! {
+    (u32, 5),
+    (u64, 6),
+}
+
 impl_pext! {
     (PdepPextMaskU8, u8, 3),
     (PdepPextMaskU16, u16, 4),
@@ -102,13 +125,19 @@ impl_pext! { (PdepPextMaskUsize, usize, 5) }
 #[cfg(target_pointer_width = "64")]
 impl_pext! { (PdepPextMaskUsize, usize, 6) }
 
-macro_rules! impl_pdep {
-    ( ($maskty:ident, $basety:ident, $lg:literal) ) => {
+macro_rules! impl_pdep_raw {
+    ( ($maskty:ident, $basety:ident) ) => {
         impl Pdep<$basety> for $basety {
             fn pdep(self, m: $basety) -> $basety {
                 self.pdep(<$maskty>::new(m))
             }
         }
+    };
+    ( $( ( $($tt:tt)* ), )* ) => { $( impl_pdep_raw!( ( $($tt)* ) ); )* };
+}
+
+macro_rules! impl_pdep {
+    ( ($maskty:ident, $basety:ident, $lg:literal) ) => {
         impl Pdep<$maskty> for $basety {
             fn pdep(self, mask: $maskty) -> $basety {
                 let mut x = self;
@@ -124,6 +153,18 @@ macro_rules! impl_pdep {
     ( $( ( $($tt:tt)* ), )* ) => { $( impl_pdep!( ( $($tt)* ) ); )* };
 }
 
+impl_pdep_raw! {
+    (PdepPextMaskU8, u8),
+    (PdepPextMaskU16, u16),
+    (PdepPextMaskU128, u128),
+}
+
+#[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+impl_pdep_raw! {
+    (PdepPextMaskU32, u32),
+    (PdepPextMaskU64, u64),
+}
+
 impl_pdep! {
     (PdepPextMaskU8, u8, 3),
     (PdepPextMaskU16, u16, 4),
@@ -132,6 +173,31 @@ impl_pdep! {
     (PdepPextMaskU128, u128, 7),
 }
 
+// BMI2 が使えるときは命令をそのまま使う。
+#[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+mod native {
+    use std::arch::x86_64::{_pdep_u32, _pdep_u64, _pext_u32, _pext_u64};
+
+    use crate::{Pdep, Pext};
+
+    impl Pdep<u32> for u32 {
+        #[inline(always)]
+        fn pdep(self, m: u32) -> u32 { unsafe { _pdep_u32(self, m) } }
+    }
+    impl Pdep<u64> for u64 {
+        #[inline(always)]
+        fn pdep(self, m: u64) -> u64 { unsafe { _pdep_u64(self, m) } }
+    }
+    impl Pext<u32> for u32 {
+        #[inline(always)]
+        fn pext(self, m: u32) -> u32 { unsafe { _pext_u32(self, m) } }
+    }
+    impl Pext<u64> for u64 {
+        #[inline(always)]
+        fn pext(self, m: u64) -> u64 { unsafe { _pext_u64(self, m) } }
+    }
+}
+
 #[test]
 fn sanity_check() {
     let x = 0b_0101_0111_0000_1001_1110_1010_0000_0010_u32;
@@ -143,3 +209,22 @@ fn sanity_check() {
     assert_eq!(x.pext(PdepPextMaskU32::new(m)), ext);
     assert_eq!(ext.pdep(m), x & m);
 }
+
+#[test]
+fn raw_and_mask() {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    let mut rng = ChaCha20Rng::from_seed([0; 32]);
+    let mut rand = || rng.gen::<u64>();
+    for _ in 0..1000 {
+        let (x, m) = (rand(), rand() & rand());
+        let mask = PdepPextMaskU64::new(m);
+        assert_eq!(x.pext(m), x.pext(mask));
+        assert_eq!(x.pdep(m), x.pdep(mask));
+        let (x, m) = (x as u32, m as u32);
+        let mask = PdepPextMaskU32::new(m);
+        assert_eq!(x.pext(m), x.pext(mask));
+        assert_eq!(x.pdep(m), x.pdep(mask));
+    }
+}
@@ -2,6 +2,7 @@ use inner::doc_inline_reexport;
 
 doc_inline_reexport! {
     bin_iter,
+    binary_trie,
     digits_iter,
     pdep_pext,
     word_set,
@@ -0,0 +1,305 @@
+use std::{
+    fmt,
+    io::{self, Read, Write},
+};
+
+pub const MAGIC: [u8; 4] = *b"NKLB";
+pub const VERSION: u32 = 1;
+
+/// バイナリ形式での読み書き。
+///
+/// `write_to` は次の形式で書き出す。整数はすべて little endian で、
+/// `usize` は `u64` として扱う。
+///
+/// ```text
+/// magic    : [u8; 4]   b"NKLB"
+/// version  : u32       1
+/// tag_len  : u64
+/// tag      : [u8; tag_len]   型の名前 (UTF-8)
+/// body_len : u64
+/// body     : [u8; body_len]  `encode` による
+/// checksum : u64       tag と body の FNV-1a (64 bit)
+/// ```
+///
+/// `body` 中では、`bool` は `u8`、`char` は `u32`、`Vec<T>` は
+/// 長さ (`u64`) に続けて各要素を並べたものとして表す。
+pub trait BinaryIo: Sized {
+    /// 型を区別するための名前。パラメータも含める。
+    fn tag() -> String;
+    fn encode(&self, enc: &mut Encoder);
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, BinaryIoError>;
+
+    fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        let tag = Self::tag();
+        let mut enc = Encoder::new();
+        self.encode(&mut enc);
+        let body = enc.into_inner();
+
+        w.write_all(&MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+        w.write_all(&(tag.len() as u64).to_le_bytes())?;
+        w.write_all(tag.as_bytes())?;
+        w.write_all(&(body.len() as u64).to_le_bytes())?;
+        w.write_all(&body)?;
+        w.write_all(&fnv1a(&[tag.as_bytes(), &body]).to_le_bytes())
+    }
+
+    fn read_from(r: &mut impl Read) -> Result<Self, BinaryIoError> {
+        let magic: [u8; 4] = read_array(r)?;
+        if magic != MAGIC {
+            return Err(BinaryIoError::Magic(magic));
+        }
+        let version = u32::from_le_bytes(read_array(r)?);
+        if version != VERSION {
+            return Err(BinaryIoError::Version(version));
+        }
+        let tag = read_chunk(r)?;
+        let expected = Self::tag();
+        if tag != expected.as_bytes() {
+            let found = String::from_utf8_lossy(&tag).into_owned();
+            return Err(BinaryIoError::Tag { expected, found });
+        }
+        let body = read_chunk(r)?;
+        let checksum = u64::from_le_bytes(read_array(r)?);
+        let actual = fnv1a(&[&tag, &body]);
+        if checksum != actual {
+            return Err(BinaryIoError::Checksum { expected: checksum, actual });
+        }
+
+        let mut dec = Decoder::new(&body);
+        let res = Self::decode(&mut dec)?;
+        if !dec.is_empty() {
+            return Err(BinaryIoError::Invalid("trailing bytes"));
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Debug)]
+pub enum BinaryIoError {
+    Io(io::Error),
+    Truncated,
+    Magic([u8; 4]),
+    Version(u32),
+    Tag { expected: String, found: String },
+    Checksum { expected: u64, actual: u64 },
+    Invalid(&'static str),
+}
+
+impl fmt::Display for BinaryIoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Truncated => write!(f, "unexpected end of data"),
+            Self::Magic(magic) => write!(f, "invalid magic number {magic:02x?}"),
+            Self::Version(version) => {
+                write!(f, "unsupported version {version} (expected {VERSION})")
+            }
+            Self::Tag { expected, found } => {
+                write!(f, "type mismatch: expected `{expected}`, found `{found}`")
+            }
+            Self::Checksum { expected, actual } => write!(
+                f,
+                "checksum mismatch: expected {expected:#018x}, actual {actual:#018x}"
+            ),
+            Self::Invalid(what) => write!(f, "invalid data: {what}"),
+        }
+    }
+}
+
+impl std::error::Error for BinaryIoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BinaryIoError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            Self::Truncated
+        } else {
+            Self::Io(e)
+        }
+    }
+}
+
+fn fnv1a(chunks: &[&[u8]]) -> u64 {
+    let mut res = 0xcbf29ce484222325_u64;
+    for &b in chunks.iter().flat_map(|c| c.iter()) {
+        res ^= b as u64;
+        res = res.wrapping_mul(0x100000001b3);
+    }
+    res
+}
+
+fn read_array<const N: usize>(r: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_chunk(r: &mut impl Read) -> Result<Vec<u8>, BinaryIoError> {
+    let len = u64::from_le_bytes(read_array(r)?);
+    // not to allocate a huge buffer for broken lengths
+    let mut buf = vec![];
+    r.take(len).read_to_end(&mut buf)?;
+    if (buf.len() as u64) < len {
+        return Err(BinaryIoError::Truncated);
+    }
+    Ok(buf)
+}
+
+pub struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn new() -> Self { Self(vec![]) }
+    fn into_inner(self) -> Vec<u8> { self.0 }
+
+    pub fn bytes(&mut self, buf: &[u8]) { self.0.extend_from_slice(buf); }
+    pub fn put<T: BinaryIo>(&mut self, x: &T) { x.encode(self); }
+}
+
+pub struct Decoder<'a>(&'a [u8]);
+
+impl<'a> Decoder<'a> {
+    fn new(buf: &'a [u8]) -> Self { Self(buf) }
+
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], BinaryIoError> {
+        if self.0.len() < len {
+            return Err(BinaryIoError::Truncated);
+        }
+        let (res, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(res)
+    }
+    pub fn get<T: BinaryIo>(&mut self) -> Result<T, BinaryIoError> {
+        T::decode(self)
+    }
+}
+
+macro_rules! impl_int {
+    ( $($ty:ty)* ) => { $(
+        impl BinaryIo for $ty {
+            fn tag() -> String { stringify!($ty).to_owned() }
+            fn encode(&self, enc: &mut Encoder) {
+                enc.bytes(&self.to_le_bytes());
+            }
+            fn decode(dec: &mut Decoder<'_>) -> Result<Self, BinaryIoError> {
+                let buf = dec.bytes(std::mem::size_of::<$ty>())?;
+                Ok(<$ty>::from_le_bytes(buf.try_into().unwrap()))
+            }
+        }
+    )* }
+}
+
+impl_int! { u8 u16 u32 u64 u128 i8 i16 i32 i64 i128 }
+
+impl BinaryIo for usize {
+    fn tag() -> String { "usize".to_owned() }
+    fn encode(&self, enc: &mut Encoder) { enc.put(&(*self as u64)); }
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, BinaryIoError> {
+        let x: u64 = dec.get()?;
+        x.try_into().map_err(|_| BinaryIoError::Invalid("usize overflow"))
+    }
+}
+
+impl BinaryIo for bool {
+    fn tag() -> String { "bool".to_owned() }
+    fn encode(&self, enc: &mut Encoder) { enc.put(&(*self as u8)); }
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, BinaryIoError> {
+        match dec.get::<u8>()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(BinaryIoError::Invalid("bool")),
+        }
+    }
+}
+
+impl BinaryIo for char {
+    fn tag() -> String { "char".to_owned() }
+    fn encode(&self, enc: &mut Encoder) { enc.put(&(*self as u32)); }
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, BinaryIoError> {
+        char::from_u32(dec.get()?).ok_or(BinaryIoError::Invalid("char"))
+    }
+}
+
+impl<T: BinaryIo> BinaryIo for Vec<T> {
+    fn tag() -> String { format!("Vec<{}>", T::tag()) }
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put(&self.len());
+        for x in self {
+            enc.put(x);
+        }
+    }
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, BinaryIoError> {
+        let len: usize = dec.get()?;
+        // each element takes at least one byte, except for ZSTs
+        let mut res = Vec::with_capacity(len.min(dec.0.len()));
+        for _ in 0..len {
+            res.push(dec.get()?);
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn round_trip<T: BinaryIo>(x: &T) -> Vec<u8> {
+        let mut buf = vec![];
+        x.write_to(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn layout() {
+        let buf = round_trip(&vec![1_u16, 0x203]);
+        let tag = b"Vec<u16>";
+        let body = [2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 3, 2];
+        let mut expected = b"NKLB\x01\x00\x00\x00".to_vec();
+        expected.extend_from_slice(&(tag.len() as u64).to_le_bytes());
+        expected.extend_from_slice(tag);
+        expected.extend_from_slice(&(body.len() as u64).to_le_bytes());
+        expected.extend_from_slice(&body);
+        expected.extend_from_slice(&fnv1a(&[tag, &body]).to_le_bytes());
+        assert_eq!(buf, expected);
+
+        let actual = Vec::<u16>::read_from(&mut buf.as_slice()).unwrap();
+        assert_eq!(actual, [1, 0x203]);
+    }
+
+    #[test]
+    fn errors() {
+        let a = vec!['a', 'β', '💡'];
+        let buf = round_trip(&a);
+        assert_eq!(Vec::<char>::read_from(&mut buf.as_slice()).unwrap(), a);
+
+        for len in 0..buf.len() {
+            let res = Vec::<char>::read_from(&mut &buf[..len]);
+            assert!(matches!(res, Err(BinaryIoError::Truncated)));
+        }
+
+        let res = Vec::<u32>::read_from(&mut buf.as_slice());
+        assert!(matches!(res, Err(BinaryIoError::Tag { .. })));
+
+        let mut broken = buf.clone();
+        *broken.last_mut().unwrap() ^= 1;
+        let res = Vec::<char>::read_from(&mut broken.as_slice());
+        assert!(matches!(res, Err(BinaryIoError::Checksum { .. })));
+
+        let mut broken = buf.clone();
+        broken[0] = b'X';
+        let res = Vec::<char>::read_from(&mut broken.as_slice());
+        assert!(matches!(res, Err(BinaryIoError::Magic(_))));
+
+        let mut broken = buf.clone();
+        broken[4] = 2;
+        let res = Vec::<char>::read_from(&mut broken.as_slice());
+        assert!(matches!(res, Err(BinaryIoError::Version(2))));
+    }
+}
@@ -1,6 +1,7 @@
 use inner::doc_inline_reexport;
 
 doc_inline_reexport! {
+    binary_io,
     input,
     readable,
 }
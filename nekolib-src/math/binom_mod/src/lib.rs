@@ -0,0 +1,187 @@
+use factors::Factors;
+use garner::CrtMod;
+use gcd_recip::GcdRecip;
+
+/// 任意の法での二項係数。
+///
+/// 法 $m$ を素冪 $p^q$ に分解し、$q=1$ なら Lucas の定理で、$q\\gt 1$ なら
+/// その一般化 (Granville) で $\\binom{n}{k}\\bmod p^q$ を求め、中国剰余定理で
+/// 合成する。前計算は $O(m)$ 時間・空間、各クエリは $O(\\omega(m)\\log(n))$ 時間。
+///
+/// # Examples
+/// ```
+/// use binom_mod::BinomMod;
+///
+/// let bm = BinomMod::new(12);
+/// assert_eq!(bm.binom(10, 3), 120 % 12);
+/// assert_eq!(bm.binom(10_u64.pow(18), 2), 0);
+/// ```
+pub struct BinomMod {
+    m: u64,
+    parts: Vec<PrimePowerBinom>,
+}
+
+struct PrimePowerBinom {
+    p: u64,
+    q: u32,
+    pq: u64,
+    // q == 1 なら i! mod p
+    // q > 1 なら p の倍数を除いた 1, 2, ..., i の積 mod p^q
+    fact: Vec<u64>,
+    fact_recip: Vec<u64>,
+}
+
+impl PrimePowerBinom {
+    fn new(p: u64, q: u32, pq: u64) -> Self {
+        let mut fact = vec![1 % pq; pq as usize];
+        for i in 1..pq {
+            let f = if q > 1 && i % p == 0 { 1 } else { i };
+            fact[i as usize] = fact[i as usize - 1] * f % pq;
+        }
+        let fact_recip = if q == 1 {
+            let mut fact_recip = vec![1 % pq; pq as usize];
+            fact_recip[pq as usize - 1] = fact[pq as usize - 1].gcd_recip(pq).1;
+            for i in (1..pq).rev() {
+                fact_recip[i as usize - 1] = fact_recip[i as usize] * i % pq;
+            }
+            fact_recip
+        } else {
+            vec![]
+        };
+        Self { p, q, pq, fact, fact_recip }
+    }
+
+    fn binom(&self, n: u64, k: u64) -> u64 {
+        if k > n {
+            return 0;
+        }
+        if self.q == 1 {
+            self.lucas(n, k)
+        } else {
+            self.granville(n, k)
+        }
+    }
+
+    fn lucas(&self, mut n: u64, mut k: u64) -> u64 {
+        let p = self.p;
+        let mut res = 1 % p;
+        while k > 0 {
+            let (ni, ki) = ((n % p) as usize, (k % p) as usize);
+            if ni < ki {
+                return 0;
+            }
+            res = res * self.fact[ni] % p * self.fact_recip[ki] % p
+                * self.fact_recip[ni - ki]
+                % p;
+            n /= p;
+            k /= p;
+        }
+        res
+    }
+
+    fn granville(&self, n: u64, k: u64) -> u64 {
+        let Self { p, q, pq, .. } = *self;
+        let (mut n, mut k, mut r) = (n, k, n - k);
+        // (p^q)! から p の倍数を除いた積は、p = 2, q >= 3 なら 1、さもなくば -1
+        let neg = !(p == 2 && q >= 3);
+        let mut e = 0;
+        let mut sign = false;
+        let mut num = 1 % pq;
+        let mut den = 1 % pq;
+        while n > 0 {
+            num = num * self.fact[(n % pq) as usize] % pq;
+            den = den * self.fact[(k % pq) as usize] % pq
+                * self.fact[(r % pq) as usize]
+                % pq;
+            if neg {
+                sign ^= ((n / pq) ^ (k / pq) ^ (r / pq)) & 1 == 1;
+            }
+            n /= p;
+            k /= p;
+            r /= p;
+            e += n - k - r;
+        }
+        if e >= q as u64 {
+            return 0;
+        }
+        let res = num * den.gcd_recip(pq).1 % pq * p.pow(e as u32) % pq;
+        if sign {
+            (pq - res) % pq
+        } else {
+            res
+        }
+    }
+}
+
+impl BinomMod {
+    pub fn new(m: u64) -> Self {
+        assert!(m > 0);
+        let parts = m
+            .factors()
+            .map(|((p, q), pq)| PrimePowerBinom::new(p, q, pq))
+            .collect();
+        Self { m, parts }
+    }
+
+    /// $\\binom{n}{k}\\bmod m$。
+    pub fn binom(&self, n: u64, k: u64) -> u64 {
+        let rs: Vec<_> =
+            self.parts.iter().map(|part| (part.binom(n, k), part.pq)).collect();
+        rs.crt_mod(self.m)
+    }
+}
+
+/// $\\binom{n}{k}\\bmod m$ ([`BinomMod`])。
+pub fn binom_mod(n: u64, k: u64, m: u64) -> u64 { BinomMod::new(m).binom(n, k) }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn pascal() {
+        let n_max = 120;
+        let mut binom = vec![vec![0_u128; n_max + 1]; n_max + 1];
+        for i in 0..=n_max {
+            binom[i][0] = 1;
+            for j in 1..=i {
+                binom[i][j] = binom[i - 1][j - 1] + binom[i - 1][j];
+            }
+        }
+
+        let ms = (1..=100).chain([
+            256, 729, 1024, 3125, 10007, 65536, 531441, 999999, 1000000,
+            1000003,
+        ]);
+        for m in ms {
+            let bm = BinomMod::new(m);
+            for (n, row) in binom.iter().enumerate() {
+                for k in 0..=n_max + 1 {
+                    let expected =
+                        row.get(k).map_or(0, |&b| (b % m as u128) as u64);
+                    let actual = bm.binom(n as u64, k as u64);
+                    assert_eq!(actual, expected, "C({n}, {k}) mod {m}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn large() {
+        // Lucas と Granville が (mod p) で一致すること
+        let n = 10_u64.pow(18);
+        for (p, q) in [(2, 10), (3, 6), (7, 3), (13, 2)] {
+            let lucas = BinomMod::new(p);
+            let granville = BinomMod::new(p.pow(q));
+            for k in (0..n).step_by(n as usize / 100 + 7) {
+                assert_eq!(granville.binom(n, k) % p, lucas.binom(n, k));
+            }
+        }
+
+        // C(2^60, 2^59) は 2 でちょうど 1 回割り切れる
+        let n = 1_u64 << 60;
+        assert_eq!(binom_mod(n, n / 2, 4), 2);
+        assert_eq!(binom_mod(n, 2, 1 << 20), 0);
+        assert_eq!(binom_mod(n, 1, 3), n % 3);
+    }
+}
@@ -712,6 +712,66 @@ impl_convolve_mod! {
     (convolve_u128_mod, u128, CrtU128Mod, [Mod0, Mod1, Mod2, Mod3, Mod4, Mod5, Mod6, Mod7, Mod8, Mod9]),
 }
 
+/// 任意の [`ModInt`] 上の畳み込み。
+///
+/// 法が NTT に適した素数であれば NTT を一回、そうでなければ複数の法での NTT
+/// と Garner のアルゴリズムを用いる。
+///
+/// NTT を一回で済ませるのは、法が 998244353 または crate 内の NTT 用の素数
+/// のいずれかに一致するときに限る。法は実行時の値で比べるので、利用者が
+/// [`NttFriendly`] を実装した型であっても、これらと異なる法なら Garner
+/// のアルゴリズムを経由する。その場合は [`convolve`] を直接呼ぶ。
+pub fn convolve_any<M: ModInt>(a: &[M], b: &[M]) -> Vec<M> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let (n, m) = (a.len(), b.len());
+    if n.min(m) <= 60 {
+        let mut res = vec![M::new(0); n + m - 1];
+        for i in 0..n {
+            for j in 0..m {
+                res[i + j] += a[i] * b[j];
+            }
+        }
+        return res;
+    }
+
+    let modulus = M::modulus_u64();
+    macro_rules! dispatch_ntt {
+        ( $($modint:ty),* ) => { $(
+            if modulus == <$modint>::MOD as u64
+                && n + m - 1 <= 1_usize << <$modint>::EXP
+            {
+                return convolve_via::<$modint, M>(a, b);
+            }
+        )* }
+    }
+    dispatch_ntt! { ModInt998244353, Mod0, Mod1, Mod2, Mod3, Mod4, Mod5, Mod6, Mod7, Mod8, Mod9 }
+
+    let a_u64 = a.iter().map(|&x| x.get_u64());
+    let b_u64 = b.iter().map(|&x| x.get_u64());
+    if let Ok(modulus) = u32::try_from(modulus) {
+        let a: Vec<_> = a_u64.map(|x: u64| x as u32).collect();
+        let b: Vec<_> = b_u64.map(|x: u64| x as u32).collect();
+        convolve_u32_mod(&a, &b, modulus).into_iter().map(M::new).collect()
+    } else {
+        // convolve_u64_mod overflows for moduli larger than 2^32
+        let a: Vec<_> = a_u64.map(|x: u64| x as u128).collect();
+        let b: Vec<_> = b_u64.map(|x: u64| x as u128).collect();
+        let modulus = modulus as u128;
+        convolve_u128_mod(&a, &b, modulus).into_iter().map(M::new).collect()
+    }
+}
+
+fn convolve_via<N: NttFriendly + 'static, M: ModInt>(
+    a: &[M],
+    b: &[M],
+) -> Vec<M> {
+    let a = a.iter().map(|&x| N::new(x.get())).collect();
+    let b = b.iter().map(|&x| N::new(x.get())).collect();
+    convolve(a, b).into_iter().map(|x: N| M::new(x.get())).collect()
+}
+
 fn ceil_pow2(n: u32) -> u32 { 32 - n.saturating_sub(1).leading_zeros() }
 
 #[test]
@@ -822,3 +882,58 @@ fn pow2p1() {
     let expected = convolve_naive(&a, &b);
     assert_eq!(convolve(a, b), expected);
 }
+
+#[test]
+fn any_modulus() {
+    use std::sync::OnceLock;
+
+    use modint::{
+        Barrett, DynamicModInt, DynamicModIntId, ModInt1000000007, Montgomery,
+        MontgomeryModInt, MontgomeryModIntId,
+    };
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    fn check<M: ModInt>(rand: &mut impl FnMut() -> u64) {
+        for (n, m) in
+            [(0_usize, 5), (3, 7), (100, 61), (300, 500), (1025, 1024)]
+        {
+            let a: Vec<_> = (0..n).map(|_| M::new(rand())).collect();
+            let b: Vec<_> = (0..m).map(|_| M::new(rand())).collect();
+            let mut expected = vec![M::new(0); (n + m).saturating_sub(1)];
+            for i in 0..n {
+                for j in 0..m {
+                    expected[i + j] += a[i] * b[j];
+                }
+            }
+            if n == 0 {
+                expected.clear();
+            }
+            assert!(convolve_any(&a, &b) == expected);
+        }
+    }
+
+    enum Id {}
+    impl DynamicModIntId for Id {
+        fn barrett() -> &'static OnceLock<Barrett> {
+            static BARRETT: OnceLock<Barrett> = OnceLock::new();
+            &BARRETT
+        }
+    }
+    impl MontgomeryModIntId for Id {
+        fn montgomery() -> &'static OnceLock<Montgomery> {
+            static MONTGOMERY: OnceLock<Montgomery> = OnceLock::new();
+            &MONTGOMERY
+        }
+    }
+    DynamicModInt::<Id>::set_modulus(1 << 30);
+    MontgomeryModInt::<Id>::set_modulus((1 << 61) - 1);
+
+    let mut rng = ChaCha20Rng::from_seed([0; 32]);
+    let mut rand = || rng.gen::<u64>();
+    check::<ModInt998244353>(&mut rand);
+    check::<ModInt1000000007>(&mut rand);
+    check::<Mod3>(&mut rand);
+    check::<DynamicModInt<Id>>(&mut rand);
+    check::<MontgomeryModInt<Id>>(&mut rand);
+}
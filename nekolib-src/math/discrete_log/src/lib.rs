@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+
+use factors::FactorsFast;
+use gcd::Gcd;
+use gcd_recip::GcdRecip;
+use modint::ModInt;
+
+/// 法 $p$ (素数) の原始根のうち最小のもの。
+pub trait PrimitiveRoot {
+    fn primitive_root(self) -> Self;
+}
+
+/// $a^x \\equiv b \\pmod{m}$ なる最小の $x\\ge 0$。
+///
+/// $a$ と $m$ が互いに素でなくてもよい。Baby-step giant-step により
+/// $O(\\sqrt{m})$ 時間。
+pub trait DiscreteLog: Sized {
+    fn discrete_log(self, b: Self, m: Self) -> Option<Self>;
+}
+
+/// $x^2 \\equiv a \\pmod{p}$ なる $x$ (素数 $p$)。
+///
+/// 解が存在すれば、小さい方の解を返す。Tonelli–Shanks により
+/// $O(\\log(p)^2)$ 時間。
+pub trait SqrtMod: Sized {
+    fn sqrt_mod(self, p: Self) -> Option<Self>;
+}
+
+/// $x^k \\equiv a \\pmod{p}$ なる $x$ (素数 $p$)。
+///
+/// $g = \\gcd(k, p-1)$ の素因数 $q$ ごとに Adleman–Manders–Miller の方法で
+/// 根を求めて合成する。$O(\\sum_{q\\mid g} (\\log(p)^2+\\sqrt{q})$ 時間程度。
+pub trait KthRootMod: Sized {
+    fn kth_root_mod(self, k: Self, p: Self) -> Option<Self>;
+}
+
+#[derive(Clone, Copy)]
+struct Mod(u64);
+
+impl Mod {
+    fn mul(self, a: u64, b: u64) -> u64 {
+        (a as u128 * b as u128 % self.0 as u128) as u64
+    }
+    fn pow(self, mut a: u64, mut e: u64) -> u64 {
+        let mut res = 1 % self.0;
+        while e > 0 {
+            if e & 1 == 1 {
+                res = self.mul(res, a);
+            }
+            a = self.mul(a, a);
+            e >>= 1;
+        }
+        res
+    }
+    fn recip(self, a: u64) -> u64 {
+        (a as u128).gcd_recip(self.0 as u128).1 as u64
+    }
+}
+
+// log_g(x), given that g has order n
+fn bsgs(md: Mod, g: u64, x: u64, n: u64) -> Option<u64> {
+    let s = n.isqrt() + 1;
+    let mut baby = HashMap::new();
+    let mut cur = 1 % md.0;
+    for j in 0..s {
+        baby.entry(cur).or_insert(j);
+        cur = md.mul(cur, g);
+    }
+    let giant = md.recip(md.pow(g, s));
+    let mut cur = x;
+    for i in 0..s {
+        if let Some(&j) = baby.get(&cur) {
+            return Some(i * s + j);
+        }
+        cur = md.mul(cur, giant);
+    }
+    None
+}
+
+fn primitive_root_u64(p: u64) -> u64 {
+    if p == 2 {
+        return 1;
+    }
+    let md = Mod(p);
+    let qs: Vec<_> = (p - 1).factors_fast().map(|((q, _), _)| q).collect();
+    (2..).find(|&g| qs.iter().all(|&q| md.pow(g, (p - 1) / q) != 1)).unwrap()
+}
+
+fn discrete_log_u64(a: u64, b: u64, mut m: u64) -> Option<u64> {
+    let (mut a, mut b) = (a % m, b % m);
+    let mut coef = 1 % m;
+    let mut add = 0;
+    loop {
+        let g = a.gcd(m);
+        if g == 1 {
+            break;
+        }
+        if b == coef {
+            return Some(add);
+        }
+        if b % g != 0 {
+            return None;
+        }
+        b /= g;
+        m /= g;
+        add += 1;
+        coef = Mod(m).mul(coef, a / g);
+        a %= m;
+    }
+    if b == coef {
+        return Some(add);
+    }
+
+    // coef * a^x = b
+    let md = Mod(m);
+    let n = m.isqrt() + 1;
+    let mut baby = HashMap::new();
+    let mut cur = b;
+    for j in 0..n {
+        baby.insert(cur, j);
+        cur = md.mul(cur, a);
+    }
+    let an = md.pow(a, n);
+    let mut cur = coef;
+    for i in 1..=n {
+        cur = md.mul(cur, an);
+        if let Some(&j) = baby.get(&cur) {
+            return Some(add + i * n - j);
+        }
+    }
+    None
+}
+
+fn sqrt_mod_u64(a: u64, p: u64) -> Option<u64> {
+    let a = a % p;
+    if a == 0 || p == 2 {
+        return Some(a);
+    }
+    let md = Mod(p);
+    if md.pow(a, (p - 1) / 2) != 1 {
+        return None;
+    }
+    let s = (p - 1).trailing_zeros();
+    let q = (p - 1) >> s;
+    let non_residue = (2..).find(|&z| md.pow(z, (p - 1) / 2) != 1).unwrap();
+    let mut z = md.pow(non_residue, q);
+    let mut x = md.pow(a, q.div_ceil(2));
+    let mut b = md.pow(a, q);
+    let mut r = s;
+    while b != 1 {
+        let i = (1..r)
+            .scan(b, |bb, i| {
+                *bb = md.mul(*bb, *bb);
+                Some((i, *bb))
+            })
+            .find(|&(_, bb)| bb == 1)
+            .unwrap()
+            .0;
+        let t = md.pow(z, 1 << (r - i - 1));
+        x = md.mul(x, t);
+        z = md.mul(t, t);
+        b = md.mul(b, z);
+        r = i;
+    }
+    Some(x.min(p - x))
+}
+
+// x^(q^e) = a, given that q^e | p-1 and a^((p-1)/q^e) = 1
+fn prime_power_root(md: Mod, a: u64, q: u64, e: u32) -> u64 {
+    let p = md.0;
+    let (mut s, mut t) = (p - 1, 0);
+    while s.is_multiple_of(q) {
+        s /= q;
+        t += 1;
+    }
+    let qe = q.pow(e);
+
+    // s * u = -1 (mod q^e)
+    let u = qe - Mod(qe).recip(s % qe);
+    let z = md.pow(a, ((s as u128 * u as u128 + 1) / qe as u128) as u64);
+    // z^(q^e) = a * w, where w is in the q-Sylow subgroup
+    let w = md.pow(md.pow(a, s), u);
+    if w == 1 {
+        return z;
+    }
+
+    // h generates the q-Sylow subgroup, whose order is q^t
+    let v = (2..).find(|&v| md.pow(v, (p - 1) / q) != 1).unwrap();
+    let h = md.pow(v, s);
+    let gamma = md.pow(h, q.pow(t - 1));
+    let h_recip = md.recip(h);
+    let w_recip = md.recip(w);
+
+    // Pohlig--Hellman: w^(-1) = h^l
+    let mut l = 0;
+    for i in 0..t {
+        let c = md.mul(w_recip, md.pow(h_recip, l));
+        let c = md.pow(c, q.pow(t - 1 - i));
+        let d = bsgs(md, gamma, c, q).unwrap();
+        l += d * q.pow(i);
+    }
+    md.mul(z, md.pow(h, l / qe))
+}
+
+fn kth_root_mod_u64(a: u64, k: u64, p: u64) -> Option<u64> {
+    let a = a % p;
+    if k == 0 {
+        return (a == 1).then_some(1);
+    }
+    if a == 0 || p == 2 {
+        return Some(a);
+    }
+    let md = Mod(p);
+    let g = k.gcd(p - 1);
+    if md.pow(a, (p - 1) / g) != 1 {
+        return None;
+    }
+
+    // b = a^u, where u * (k/g) = 1 (mod (p-1)/g), and then x^g = b
+    let u = Mod((p - 1) / g).recip(k / g % ((p - 1) / g));
+    let b = md.pow(a, u);
+    let mut root = b;
+    let mut order = 1;
+    for ((q, e), qe) in g.factors_fast() {
+        let y = prime_power_root(md, b, q, e);
+        // alpha * order + beta * qe = 1, root^(order * qe) = b
+        let alpha = Mod(qe).recip(order % qe);
+        let neg_beta =
+            ((alpha as u128 * order as u128 - 1) / qe as u128) as u64;
+        root = md.mul(md.pow(md.recip(root), neg_beta), md.pow(y, alpha));
+        order *= qe;
+    }
+    Some(root)
+}
+
+macro_rules! impl_uint {
+    ( $($ty:ty)* ) => { $(
+        impl PrimitiveRoot for $ty {
+            fn primitive_root(self) -> Self {
+                primitive_root_u64(self as u64) as $ty
+            }
+        }
+        impl DiscreteLog for $ty {
+            fn discrete_log(self, b: Self, m: Self) -> Option<Self> {
+                discrete_log_u64(self as u64, b as u64, m as u64).map(|x| x as $ty)
+            }
+        }
+        impl SqrtMod for $ty {
+            fn sqrt_mod(self, p: Self) -> Option<Self> {
+                sqrt_mod_u64(self as u64, p as u64).map(|x| x as $ty)
+            }
+        }
+        impl KthRootMod for $ty {
+            fn kth_root_mod(self, k: Self, p: Self) -> Option<Self> {
+                kth_root_mod_u64(self as u64, k as u64, p as u64).map(|x| x as $ty)
+            }
+        }
+    )* };
+}
+
+impl_uint! { u8 u16 u32 u64 usize }
+
+/// 法の原始根 ([`PrimitiveRoot`])。
+pub fn primitive_root<M: ModInt>() -> M {
+    M::new(primitive_root_u64(M::modulus_u64()))
+}
+
+/// $a^x = b$ なる最小の $x\\ge 0$ ([`DiscreteLog`])。
+pub fn discrete_log<M: ModInt>(a: M, b: M) -> Option<u64> {
+    discrete_log_u64(a.get_u64(), b.get_u64(), M::modulus_u64())
+}
+
+/// $x^2 = a$ なる $x$ ([`SqrtMod`])。法は素数とする。
+pub fn sqrt_mod<M: ModInt>(a: M) -> Option<M> {
+    sqrt_mod_u64(a.get_u64(), M::modulus_u64()).map(M::new)
+}
+
+/// $x^k = a$ なる $x$ ([`KthRootMod`])。法は素数とする。
+pub fn kth_root_mod<M: ModInt>(a: M, k: u64) -> Option<M> {
+    kth_root_mod_u64(a.get_u64(), k, M::modulus_u64()).map(M::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use modint::{ModInt998244353, StaticModInt};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    const PRIMES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 31, 41, 97, 101, 193];
+
+    #[test]
+    fn proot() {
+        for p in PRIMES {
+            let md = Mod(p);
+            let g = p.primitive_root();
+            let order = (1..p).find(|&i| md.pow(g, i) == 1).unwrap();
+            assert_eq!(order, p - 1);
+            assert!((1..g).all(|h| (1..p - 1).any(|i| md.pow(h, i) == 1)));
+        }
+        assert_eq!(998244353_u32.primitive_root(), 3);
+        assert_eq!(1_000_000_007_u32.primitive_root(), 5);
+        assert_eq!(primitive_root::<ModInt998244353>().get(), 3);
+    }
+
+    #[test]
+    fn dlog() {
+        for m in 1..=60_u32 {
+            for a in 0..m {
+                for b in 0..m {
+                    let mut cur = 1 % m;
+                    let mut expected = None;
+                    for x in 0..=m {
+                        if cur == b {
+                            expected = Some(x);
+                            break;
+                        }
+                        cur = cur * a % m;
+                    }
+                    assert_eq!(a.discrete_log(b, m), expected, "{a} {b} {m}");
+                }
+            }
+        }
+
+        type Mi = ModInt998244353;
+        let a = Mi::new(3);
+        let x = 123456789_u64;
+        assert_eq!(discrete_log(a, a.pow(x)), Some(x));
+        let m = 10_u64.pow(12) + 39;
+        let g = m.primitive_root();
+        let x = 987654321012;
+        assert_eq!(g.discrete_log(Mod(m).pow(g, x), m), Some(x));
+    }
+
+    #[test]
+    fn sqrt() {
+        for p in PRIMES {
+            for a in 0..p {
+                let expected = (0..p).find(|&x| x * x % p == a);
+                assert_eq!(a.sqrt_mod(p), expected);
+            }
+        }
+
+        type Mi = StaticModInt<1_000_000_007>;
+        for a in 0..1000 {
+            let a = Mi::new(a);
+            if let Some(x) = sqrt_mod(a) {
+                assert_eq!(x * x, a);
+            } else {
+                assert_ne!(a.pow(500_000_003_u32), Mi::new(1));
+            }
+        }
+        let p = (1 << 62) - 57;
+        let x = 1234567890123456789_u64;
+        let a = Mod(p).mul(x, x);
+        assert_eq!(a.sqrt_mod(p), Some(x.min(p - x)));
+    }
+
+    #[test]
+    fn kth_root() {
+        for p in PRIMES {
+            let md = Mod(p);
+            for k in 0..=2 * p {
+                for a in 0..p {
+                    let exists = (0..p).any(|x| md.pow(x, k) == a);
+                    let actual = a.kth_root_mod(k, p);
+                    assert_eq!(actual.is_some(), exists, "{a} {k} {p}");
+                    if let Some(x) = actual {
+                        assert_eq!(md.pow(x, k), a);
+                    }
+                }
+            }
+        }
+
+        type Mi = ModInt998244353;
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        for k in [2, 3, 7, 17, 119, 1 << 23, 998244352, 1 << 40] {
+            for _ in 0..20 {
+                let a = Mi::new(rng.gen::<u32>()).pow(k);
+                let x = kth_root_mod(a, k).unwrap();
+                assert_eq!(x.pow(k), a);
+            }
+        }
+    }
+}
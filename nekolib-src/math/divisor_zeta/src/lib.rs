@@ -0,0 +1,151 @@
+//! 約数・倍数に関するゼータ変換・メビウス変換。
+//!
+//! いずれも添字 $1, 2, \\dots, n$ を対象とし、`a[0]` は使わない。`ls` は
+//! $n$ 以上まで篩ったものとする。
+
+use std::ops::{AddAssign, Mul, SubAssign};
+
+use linear_sieve::LinearSieve;
+
+fn primes(ls: &LinearSieve, n: usize) -> impl Iterator<Item = usize> + '_ {
+    assert!(
+        n <= ls.limit(),
+        "the sieve must cover the indices: the limit is {} but n is {n}",
+        ls.limit()
+    );
+    ls.primes().take_while(move |&p| p <= n)
+}
+
+/// $\\hat{a}\_i = \\sum_{d\\mid i} a_d$。
+pub fn divisor_zeta<T: Copy + AddAssign>(a: &mut [T], ls: &LinearSieve) {
+    let n = a.len().saturating_sub(1);
+    for p in primes(ls, n) {
+        for i in 1..=n / p {
+            let tmp = a[i];
+            a[i * p] += tmp;
+        }
+    }
+}
+
+/// [`divisor_zeta`] の逆変換。
+pub fn divisor_mobius<T: Copy + SubAssign>(a: &mut [T], ls: &LinearSieve) {
+    let n = a.len().saturating_sub(1);
+    for p in primes(ls, n) {
+        for i in (1..=n / p).rev() {
+            let tmp = a[i];
+            a[i * p] -= tmp;
+        }
+    }
+}
+
+/// $\\hat{a}\_i = \\sum_{i\\mid j} a_j$。
+pub fn multiple_zeta<T: Copy + AddAssign>(a: &mut [T], ls: &LinearSieve) {
+    let n = a.len().saturating_sub(1);
+    for p in primes(ls, n) {
+        for i in (1..=n / p).rev() {
+            let tmp = a[i * p];
+            a[i] += tmp;
+        }
+    }
+}
+
+/// [`multiple_zeta`] の逆変換。
+pub fn multiple_mobius<T: Copy + SubAssign>(a: &mut [T], ls: &LinearSieve) {
+    let n = a.len().saturating_sub(1);
+    for p in primes(ls, n) {
+        for i in 1..=n / p {
+            let tmp = a[i * p];
+            a[i] -= tmp;
+        }
+    }
+}
+
+/// $c_k = \\sum_{\\gcd(i, j) = k} a_i b_j$。
+pub fn gcd_convolve<T>(a: &[T], b: &[T], ls: &LinearSieve) -> Vec<T>
+where
+    T: Copy + AddAssign + SubAssign + Mul<Output = T>,
+{
+    assert_eq!(a.len(), b.len());
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    multiple_zeta(&mut a, ls);
+    multiple_zeta(&mut b, ls);
+    let mut c: Vec<_> = a.into_iter().zip(b).map(|(x, y)| x * y).collect();
+    multiple_mobius(&mut c, ls);
+    c
+}
+
+/// $c_k = \\sum_{\\operatorname{lcm}(i, j) = k} a_i b_j$ ($k\\le n$)。
+pub fn lcm_convolve<T>(a: &[T], b: &[T], ls: &LinearSieve) -> Vec<T>
+where
+    T: Copy + AddAssign + SubAssign + Mul<Output = T>,
+{
+    assert_eq!(a.len(), b.len());
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    divisor_zeta(&mut a, ls);
+    divisor_zeta(&mut b, ls);
+    let mut c: Vec<_> = a.into_iter().zip(b).map(|(x, y)| x * y).collect();
+    divisor_mobius(&mut c, ls);
+    c
+}
+
+#[cfg(test)]
+mod tests {
+    use gcd::Gcd;
+    use modint::ModInt998244353;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    #[test]
+    fn random() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut rand = || rng.gen_range(-100..=100_i64);
+        let ls = LinearSieve::new(300);
+        for n in [0, 1, 2, 10, 97, 300] {
+            let a: Vec<_> = (0..=n).map(|_| rand()).collect();
+            let b: Vec<_> = (0..=n).map(|_| rand()).collect();
+
+            let mut zeta = a.clone();
+            divisor_zeta(&mut zeta, &ls);
+            for (i, &zi) in zeta.iter().enumerate().skip(1) {
+                let expected: i64 =
+                    (1..=i).filter(|d| i % d == 0).map(|d| a[d]).sum();
+                assert_eq!(zi, expected);
+            }
+            divisor_mobius(&mut zeta, &ls);
+            assert_eq!(zeta[1..], a[1..]);
+
+            let mut zeta = a.clone();
+            multiple_zeta(&mut zeta, &ls);
+            for (i, &zi) in zeta.iter().enumerate().skip(1) {
+                let expected: i64 = (i..=n).step_by(i).map(|j| a[j]).sum();
+                assert_eq!(zi, expected);
+            }
+            multiple_mobius(&mut zeta, &ls);
+            assert_eq!(zeta[1..], a[1..]);
+
+            let mut expected_gcd = vec![0; n + 1];
+            let mut expected_lcm = vec![0; n + 1];
+            for (i, &ai) in a.iter().enumerate().skip(1) {
+                for (j, &bj) in b.iter().enumerate().skip(1) {
+                    let g = i.gcd(j);
+                    expected_gcd[g] += ai * bj;
+                    if i / g * j <= n {
+                        expected_lcm[i / g * j] += ai * bj;
+                    }
+                }
+            }
+            assert_eq!(gcd_convolve(&a, &b, &ls)[1..], expected_gcd[1..]);
+            assert_eq!(lcm_convolve(&a, &b, &ls)[1..], expected_lcm[1..]);
+
+            type Mi = ModInt998244353;
+            let am: Vec<_> = a.iter().map(|&x| Mi::new(x)).collect();
+            let bm: Vec<_> = b.iter().map(|&x| Mi::new(x)).collect();
+            let actual = gcd_convolve(&am, &bm, &ls);
+            assert!((1..=n).all(|k| actual[k] == Mi::new(expected_gcd[k])));
+        }
+    }
+}
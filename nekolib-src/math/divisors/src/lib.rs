@@ -1,3 +1,5 @@
+use factors::FactorsFast;
+
 pub trait Divisors: Sized {
     fn divisors(self) -> impl Iterator<Item = Self>;
 }
@@ -30,7 +32,44 @@ macro_rules! impl_uint {
     )* };
 }
 
-impl_uint! { u8 u16 u32 u64 u128 usize }
+impl_uint! { u8 u16 u32 u128 }
+
+// 試し割りが重くなる大きさの入力は、素因数分解を経由する。
+const FAST_THRESHOLD: u64 = 1 << 32;
+
+fn divisors_fast(n: u64) -> Vec<u64> {
+    let mut res = vec![1];
+    for ((p, e), _) in n.factors_fast() {
+        let len = res.len();
+        let mut pp = 1;
+        for _ in 0..e {
+            pp *= p;
+            for i in 0..len {
+                res.push(res[i] * pp);
+            }
+        }
+    }
+    res.sort_unstable();
+    res
+}
+
+macro_rules! impl_uint_fast {
+    ( $($ty:ty)* ) => { $(
+        impl Divisors for $ty {
+            fn divisors(self) -> impl Iterator<Item = Self> {
+                let n = self;
+                let (small, large) = if (n as u64) < FAST_THRESHOLD {
+                    (Some((n as u32).divisors().map(|d| d as $ty)), None)
+                } else {
+                    (None, Some(divisors_fast(n as u64).into_iter().map(|d| d as $ty)))
+                };
+                small.into_iter().flatten().chain(large.into_iter().flatten())
+            }
+        }
+    )* };
+}
+
+impl_uint_fast! { u64 usize }
 
 #[test]
 fn sanity_check() {
@@ -42,3 +81,39 @@ fn sanity_check() {
         assert!(actual.eq(expected));
     }
 }
+
+#[test]
+fn large() {
+    let naive = |n: u64| -> Vec<u64> {
+        let mut res = vec![];
+        let mut i = 1;
+        while i * i <= n {
+            if n.is_multiple_of(i) {
+                res.push(i);
+                if i * i != n {
+                    res.push(n / i);
+                }
+            }
+            i += 1;
+        }
+        res.sort_unstable();
+        res
+    };
+    for n in [
+        1_u64 << 32,
+        (1 << 32) + 1,
+        6983776800,
+        1000000007 * 3 * 3,
+        999999999999,
+    ] {
+        assert!(n.divisors().eq(naive(n)));
+    }
+
+    let n = 897612484786617600_u64; // highly composite
+    let actual: Vec<_> = n.divisors().collect();
+    assert_eq!(actual.len(), 103680);
+    assert!(actual.windows(2).all(|w| w[0] < w[1]));
+    assert!(actual.iter().all(|&d| n.is_multiple_of(d)));
+    let p = 1000000007_u64;
+    assert!((p * 998244353).divisors().eq([1, 998244353, p, p * 998244353]));
+}
@@ -24,8 +24,9 @@ impl<M: ModInt> FactorialTable<M> {
     pub fn factorial(&self, i: usize) -> M { self.factorial[i] }
     pub fn factorial_recip(&self, i: usize) -> M { self.factorial_recip[i] }
 
+    /// $\\binom{i}{j}$。$i$ が表の範囲外なら $0$ を返す。
     pub fn binom(&self, i: usize, j: usize) -> M {
-        if j <= i {
+        if j <= i && i < self.factorial.len() {
             self.factorial[i]
                 * self.factorial_recip[j]
                 * self.factorial_recip[i - j]
@@ -33,8 +34,54 @@ impl<M: ModInt> FactorialTable<M> {
             M::new(0)
         }
     }
+    /// $\\binom{i}{j}^{-1}$。$j\\gt i$ や $i$ が表の範囲外なら $0$ を返す。
     pub fn binom_recip(&self, i: usize, j: usize) -> M {
-        self.factorial_recip[i] * self.factorial[j] * self.factorial[i - j]
+        if j <= i && i < self.factorial.len() {
+            self.factorial_recip[i] * self.factorial[j] * self.factorial[i - j]
+        } else {
+            M::new(0)
+        }
+    }
+
+    /// $\\binom{-i}{j} = (-1)^j\\binom{i+j-1}{j}$。
+    pub fn binom_neg(&self, i: usize, j: usize) -> M {
+        if i == 0 {
+            return M::new((j == 0) as u32);
+        }
+        let Some(k) = (i - 1).checked_add(j) else {
+            return M::new(0);
+        };
+        let res = self.binom(k, j);
+        if j & 1 == 0 {
+            res
+        } else {
+            -res
+        }
+    }
+
+    /// 多項係数 $\\binom{k_1+\\dots+k_n}{k_1, \\dots, k_n}$。
+    pub fn multinomial(&self, ks: &[usize]) -> M {
+        let sum = ks.iter().try_fold(0_usize, |acc, &k| acc.checked_add(k));
+        let Some(sum) = sum.filter(|&sum| sum < self.factorial.len()) else {
+            return M::new(0);
+        };
+        ks.iter()
+            .fold(self.factorial[sum], |acc, &k| acc * self.factorial_recip[k])
+    }
+
+    /// Catalan 数 $\\frac{1}{i+1}\\binom{2i}{i}$。
+    pub fn catalan(&self, i: usize) -> M {
+        let len = self.factorial.len();
+        let Some(i2) = i.checked_mul(2).filter(|&i2| i2 < len) else {
+            return M::new(0);
+        };
+        if i + 1 >= len {
+            // i = 0 with the table of 0! only
+            return M::new(1);
+        }
+        self.factorial[i2]
+            * self.factorial_recip[i]
+            * self.factorial_recip[i + 1]
     }
 
     pub fn recip(&self, i: usize) -> M {
@@ -53,3 +100,36 @@ fn sanity_check() {
     assert_eq!(ft.binom_recip(5, 2), Mi::new(10).recip());
     assert_eq!(ft.recip(10), Mi::new(10).recip());
 }
+
+#[test]
+fn combinatorics() {
+    type Mi = modint::ModInt998244353;
+
+    let ft = FactorialTable::<Mi>::new(10);
+    assert_eq!(ft.binom(11, 3), Mi::new(0));
+    assert_eq!(ft.binom(3, 5), Mi::new(0));
+    assert_eq!(ft.binom_recip(3, 5), Mi::new(0));
+    assert_eq!(ft.binom_recip(12, 5), Mi::new(0));
+
+    // (1+x)^(-3) = 1 - 3x + 6x^2 - 10x^3 + ...
+    let expected = [1, -3, 6, -10, 15, -21].map(|c: i32| Mi::new(c));
+    let actual: Vec<_> = (0..6).map(|j| ft.binom_neg(3, j)).collect();
+    assert_eq!(actual, expected);
+    assert_eq!(ft.binom_neg(0, 0), Mi::new(1));
+    assert_eq!(ft.binom_neg(0, 3), Mi::new(0));
+
+    assert_eq!(ft.multinomial(&[2, 3, 4]), Mi::new(1260));
+    assert_eq!(ft.multinomial(&[]), Mi::new(1));
+    assert_eq!(ft.multinomial(&[5, 6]), Mi::new(0));
+
+    let catalan: Vec<_> = (0..7).map(|i| ft.catalan(i)).collect();
+    assert_eq!(catalan, [1, 1, 2, 5, 14, 42, 0].map(Mi::new));
+
+    // out-of-range arguments must not overflow or index out of bounds
+    assert_eq!(ft.binom_neg(usize::MAX, 2), Mi::new(0));
+    assert_eq!(ft.multinomial(&[usize::MAX, 1]), Mi::new(0));
+    assert_eq!(ft.catalan(usize::MAX), Mi::new(0));
+    let ft = FactorialTable::<Mi>::new(0);
+    assert_eq!(ft.catalan(0), Mi::new(1));
+    assert_eq!(ft.catalan(1), Mi::new(0));
+}
@@ -1,3 +1,6 @@
+use gcd::Gcd;
+use modint::Montgomery;
+
 pub trait Factors: Sized {
     fn factors(self) -> impl Iterator<Item = ((Self, u32), Self)>;
 }
@@ -59,6 +62,182 @@ macro_rules! impl_uint {
 
 impl_uint! { u8 u16 u32 u64 u128 usize }
 
+/// 決定的 Miller–Rabin 素数判定。
+pub trait IsPrime {
+    #[allow(clippy::wrong_self_convention)]
+    fn is_prime(self) -> bool;
+}
+
+/// Pollard の $\\rho$ 法による素因数分解。[`Factors`] と同じ形式で返す。
+pub trait FactorsFast: Sized {
+    fn factors_fast(self) -> impl Iterator<Item = ((Self, u32), Self)>;
+}
+
+const SMALL_PRIMES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+fn is_prime_u64(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in SMALL_PRIMES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    if n < 41 * 41 {
+        return true;
+    }
+
+    let mont = Montgomery::new(n);
+    let one = mont.encode(1);
+    let minus_one = mont.encode(n - 1);
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    let pow = |a: u64, mut e: u64| {
+        let mut res = one;
+        let mut dbl = a;
+        while e > 0 {
+            if e & 1 != 0 {
+                res = mont.mul(res, dbl);
+            }
+            dbl = mont.mul(dbl, dbl);
+            e >>= 1;
+        }
+        res
+    };
+    [2, 325, 9375, 28178, 450775, 9780504, 1795265022].into_iter().all(|a| {
+        let a = a % n;
+        if a == 0 {
+            return true;
+        }
+        let mut x = pow(mont.encode(a), d);
+        if x == one || x == minus_one {
+            return true;
+        }
+        for _ in 1..s {
+            x = mont.mul(x, x);
+            if x == minus_one {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+// a non-trivial factor of odd composite n
+fn pollard_rho(n: u64) -> u64 {
+    let mont = Montgomery::new(n);
+    let add = |x: u64, y: u64| {
+        let (z, carry) = x.overflowing_add(y);
+        if carry || z >= n {
+            z.wrapping_sub(n)
+        } else {
+            z
+        }
+    };
+    let diff = |x: u64, y: u64| x.abs_diff(y);
+    let m = 128;
+    for c in 1.. {
+        let c = mont.encode(c);
+        let f = |x: u64| add(mont.mul(x, x), c);
+        let x0 = mont.encode(2);
+        let mut x = x0;
+        let mut y = x0;
+        let mut ys = x0;
+        let mut q = mont.encode(1);
+        let mut g = 1;
+        let mut r = 1;
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+            let mut k = 0;
+            while k < r && g == 1 {
+                ys = y;
+                for _ in 0..m.min(r - k) {
+                    y = f(y);
+                    q = mont.mul(q, diff(x, y));
+                }
+                g = q.gcd(n);
+                k += m;
+            }
+            r *= 2;
+        }
+        if g == n {
+            g = 1;
+            while g == 1 {
+                ys = f(ys);
+                g = diff(x, ys).gcd(n);
+            }
+        }
+        if g != n {
+            return g;
+        }
+    }
+    unreachable!()
+}
+
+fn factors_dup_u64(mut n: u64) -> Vec<u64> {
+    let mut res = vec![];
+    if n <= 1 {
+        return res;
+    }
+    for p in SMALL_PRIMES {
+        while n.is_multiple_of(p) {
+            res.push(p);
+            n /= p;
+        }
+    }
+    let mut stack = vec![n];
+    while let Some(n) = stack.pop() {
+        if n == 1 {
+            continue;
+        }
+        if is_prime_u64(n) {
+            res.push(n);
+            continue;
+        }
+        let d = pollard_rho(n);
+        stack.push(d);
+        stack.push(n / d);
+    }
+    res.sort_unstable();
+    res
+}
+
+fn factors_u64(n: u64) -> Vec<((u64, u32), u64)> {
+    let mut res: Vec<((u64, u32), u64)> = vec![];
+    for p in factors_dup_u64(n) {
+        match res.last_mut() {
+            Some(((q, e), qq)) if *q == p => {
+                *e += 1;
+                *qq *= p;
+            }
+            _ => res.push(((p, 1), p)),
+        }
+    }
+    res
+}
+
+macro_rules! impl_fast {
+    ( $($ty:ty)* ) => { $(
+        impl IsPrime for $ty {
+            fn is_prime(self) -> bool { is_prime_u64(self as u64) }
+        }
+
+        impl FactorsFast for $ty {
+            fn factors_fast(self) -> impl Iterator<Item = ((Self, u32), Self)> {
+                factors_u64(self as u64)
+                    .into_iter()
+                    .map(|((p, e), pp)| ((p as $ty, e), pp as $ty))
+            }
+        }
+    )* };
+}
+
+impl_fast! { u8 u16 u32 u64 usize }
+
 #[test]
 fn sanity_check() {
     assert!(0_u32.factors_dup().eq(None));
@@ -108,3 +287,33 @@ fn sanity_check() {
         }
     }
 }
+
+#[test]
+fn fast() {
+    let n_max = 10000_u64;
+    for n in 0..=n_max {
+        assert!(n.factors_fast().eq(n.factors()));
+        assert_eq!(n.is_prime(), n.factors().map(|(pe, _)| pe).eq([(n, 1)]));
+    }
+
+    let p = (1_u64 << 61) - 1;
+    assert!(p.is_prime());
+    assert!(p.factors_fast().eq([((p, 1), p)]));
+    let n = 999999999999999989_u64;
+    assert!(n.is_prime());
+    let carmichael = 3825123056546413051_u64; // 149491 * 747451 * 34233211
+    assert!(!carmichael.is_prime());
+    assert!(carmichael.factors_fast().map(|((p, e), _)| (p, e)).eq([
+        (149491, 1),
+        (747451, 1),
+        (34233211, 1)
+    ]));
+    let n = 1000000007_u64 * 1000000007;
+    assert!(n.factors_fast().eq([((1000000007, 2), n)]));
+    let n = 1000000007_u64 * 998244353;
+    assert!(n.factors_fast().map(|((p, _), _)| p).eq([998244353, 1000000007]));
+    assert!(u64::MAX
+        .factors_fast()
+        .map(|((p, _), _)| p)
+        .eq([3, 5, 17, 257, 641, 65537, 6700417]));
+}
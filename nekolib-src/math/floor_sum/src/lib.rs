@@ -0,0 +1,335 @@
+use monoid::Monoid;
+
+/// $\\sum_{i=0}^{n-1} \\lfloor (ai+b)/m\\rfloor$。
+///
+/// $n\\ge 0$, $m\\ge 1$ とする。$a$, $b$ は負でもよい。
+/// $O(\\log(m))$ 時間。
+///
+/// # Examples
+/// ```
+/// use floor_sum::floor_sum;
+///
+/// assert_eq!(floor_sum(4, 10, 6, 3), 0 + 0 + 1 + 2);
+/// assert_eq!(floor_sum(3, 2, -1, 0), 0 - 1 - 1);
+/// ```
+pub fn floor_sum(n: i64, m: i64, a: i64, b: i64) -> i64 {
+    assert!(n >= 0 && m >= 1);
+    let (n, m) = (n as u64, m as u64);
+    let mut res = 0_u64;
+    let a = if a < 0 {
+        let a2 = a.rem_euclid(m as i64) as u64;
+        let q = a2.wrapping_sub(a as u64) / m;
+        res = res.wrapping_sub(n.wrapping_mul(n.wrapping_sub(1)) / 2 * q);
+        a2
+    } else {
+        a as u64
+    };
+    let b = if b < 0 {
+        let b2 = b.rem_euclid(m as i64) as u64;
+        let q = b2.wrapping_sub(b as u64) / m;
+        res = res.wrapping_sub(n.wrapping_mul(q));
+        b2
+    } else {
+        b as u64
+    };
+    res.wrapping_add(floor_sum_unsigned(n, m, a, b)) as i64
+}
+
+fn floor_sum_unsigned(mut n: u64, mut m: u64, mut a: u64, mut b: u64) -> u64 {
+    let mut res = 0_u64;
+    loop {
+        if a >= m {
+            let tri = n.wrapping_mul(n.wrapping_sub(1)) / 2;
+            res = res.wrapping_add(tri.wrapping_mul(a / m));
+            a %= m;
+        }
+        if b >= m {
+            res = res.wrapping_add(n.wrapping_mul(b / m));
+            b %= m;
+        }
+        let y_max = a.wrapping_mul(n).wrapping_add(b);
+        if y_max < m {
+            break;
+        }
+        n = y_max / m;
+        b = y_max % m;
+        std::mem::swap(&mut m, &mut a);
+    }
+    res
+}
+
+/// 万能ユークリッド。
+///
+/// $y_i = \\lfloor (ai+b)/m\\rfloor$ とし、格子上の経路
+/// $U^{y_0} R\\, U^{y_1-y_0} R \\cdots U^{y_{n-1}-y_{n-2}} R$
+/// に沿って `u`, `r` を並べた積を返す。すなわち、$i$ 番目の $R$ の手前には
+/// ちょうど $y_i$ 個の $U$ がある。
+///
+/// モノイドの演算を $O(\\log(\\max\\{n, m, a\\})^2)$ 回程度行う。
+///
+/// # Examples
+/// ```
+/// use floor_sum::universal_euclid;
+/// use monoid::{Associative, BinaryOp, Identity};
+///
+/// struct Concat;
+/// impl BinaryOp for Concat {
+///     type Set = String;
+///     fn op(&self, lhs: &String, rhs: &String) -> String { lhs.clone() + rhs }
+/// }
+/// impl Identity for Concat {
+///     fn id(&self) -> String { "".to_owned() }
+/// }
+/// impl Associative for Concat {}
+///
+/// let (u, r) = ("U".to_owned(), "R".to_owned());
+/// // floor((2i+1)/3) = 0, 1, 1, 2, 3
+/// let path = universal_euclid(&Concat, 5, 3, 2, 1, &u, &r);
+/// assert_eq!(path, "RURRURUR");
+/// ```
+pub fn universal_euclid<O: Monoid>(
+    monoid: &O,
+    n: u64,
+    m: u64,
+    a: u64,
+    b: u64,
+    u: &O::Set,
+    r: &O::Set,
+) -> O::Set
+where
+    O::Set: Clone,
+{
+    assert!(m >= 1);
+    if n == 0 {
+        return monoid.id();
+    }
+    let head = monoid.op(&pow(monoid, u, b / m), r);
+    let tail = euclid(monoid, a, m, b % m, n - 1, u, r);
+    monoid.op(&head, &tail)
+}
+
+// for x = 1, ..., l, U^(floor((px+r)/q) - floor((p(x-1)+r)/q)) R, where r < q
+fn euclid<O: Monoid>(
+    monoid: &O,
+    p: u64,
+    q: u64,
+    r: u64,
+    l: u64,
+    u: &O::Set,
+    rr: &O::Set,
+) -> O::Set
+where
+    O::Set: Clone,
+{
+    if l == 0 {
+        return monoid.id();
+    }
+    if p >= q {
+        let rr = monoid.op(&pow(monoid, u, p / q), rr);
+        return euclid(monoid, p % q, q, r, l, u, &rr);
+    }
+    let m = ((l as u128 * p as u128 + r as u128) / q as u128) as u64;
+    if m == 0 {
+        return pow(monoid, rr, l);
+    }
+    let cnt = l - ((q as u128 * m as u128 - r as u128 - 1) / p as u128) as u64;
+    let head = monoid.op(&pow(monoid, rr, (q - r - 1) / p), u);
+    let mid = euclid(monoid, q, p, (q - r - 1) % p, m - 1, rr, u);
+    monoid.op(&monoid.op(&head, &mid), &pow(monoid, rr, cnt))
+}
+
+fn pow<O: Monoid>(monoid: &O, x: &O::Set, mut k: u64) -> O::Set
+where
+    O::Set: Clone,
+{
+    let mut res = monoid.id();
+    let mut x = x.clone();
+    while k > 0 {
+        if k & 1 != 0 {
+            res = monoid.op(&res, &x);
+        }
+        k >>= 1;
+        if k > 0 {
+            x = monoid.op(&x, &x);
+        }
+    }
+    res
+}
+
+/// 格子点 $(x_0, y_0)$, $(x_1, y_1)$ を結ぶ線分の下側の格子点の個数。
+///
+/// $x_0\\le x\\le x_1$ かつ $0\\le y$ かつ $(x, y)$ が線分の下側 (線分上を含む)
+/// にあるものを数える。$x_0\\le x_1$ かつ $y_0, y_1\\ge 0$ とする。
+/// $x_0 = x_1$ のときは $0\\le y\\le\\max\\{y_0, y_1\\}$ を数える。
+///
+/// # Examples
+/// ```
+/// use floor_sum::lattice_points_under_segment;
+///
+/// // (0, 0), (1, 0), (2, 0), (2, 1), (3, 0), (3, 1), (3, 2)
+/// assert_eq!(lattice_points_under_segment((0, 0), (3, 2)), 7);
+/// ```
+pub fn lattice_points_under_segment(
+    (x0, y0): (i64, i64),
+    (x1, y1): (i64, i64),
+) -> i64 {
+    assert!(x0 <= x1 && y0 >= 0 && y1 >= 0);
+    if x0 == x1 {
+        return y0.max(y1) + 1;
+    }
+    let dx = x1 - x0;
+    floor_sum(dx + 1, dx, y1 - y0, y0 * dx) + dx + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use monoid::{Associative, BinaryOp, Identity};
+
+    use crate::*;
+
+    fn floor_div(a: i64, b: i64) -> i64 { a.div_euclid(b) }
+
+    #[test]
+    fn naive() {
+        for n in 0..20 {
+            for m in 1..20 {
+                for a in -20..20 {
+                    for b in -20..20 {
+                        let expected: i64 =
+                            (0..n).map(|i| floor_div(a * i + b, m)).sum();
+                        assert_eq!(floor_sum(n, m, a, b), expected);
+                    }
+                }
+            }
+        }
+        let n = 10_i64.pow(9);
+        let expected = (n - 1) * n / 2 * 3 + n * 2;
+        assert_eq!(floor_sum(n, 1, 3, 2), expected);
+    }
+
+    // (#R, #U, sum y, sum y^2, sum i y)
+    struct Sums;
+    type S = (u64, u64, u64, u64, u64);
+    impl BinaryOp for Sums {
+        type Set = S;
+        fn op(
+            &self,
+            &(xa, ya, sa, qa, ta): &S,
+            &(xb, yb, sb, qb, tb): &S,
+        ) -> S {
+            // wrapping, since only the sum of y is checked for large n
+            let mul = |x: u64, y: u64| x.wrapping_mul(y);
+            let tri = mul(xb, xb.wrapping_sub(1)) / 2;
+            let q = mul(2 * ya, sb).wrapping_add(mul(mul(ya, ya), xb));
+            let t = mul(mul(xa, ya), xb)
+                .wrapping_add(mul(xa, sb))
+                .wrapping_add(mul(ya, tri));
+            (
+                xa + xb,
+                ya + yb,
+                sa + sb + ya * xb,
+                qa.wrapping_add(qb).wrapping_add(q),
+                ta.wrapping_add(tb).wrapping_add(t),
+            )
+        }
+    }
+    impl Identity for Sums {
+        fn id(&self) -> S { (0, 0, 0, 0, 0) }
+    }
+    impl Associative for Sums {}
+
+    const MOD: u64 = 998244353;
+    type Mat = [[u64; 2]; 2];
+    struct MatMul;
+    impl BinaryOp for MatMul {
+        type Set = Mat;
+        fn op(&self, a: &Mat, b: &Mat) -> Mat {
+            let mut c = [[0; 2]; 2];
+            for (ci, ai) in c.iter_mut().zip(a) {
+                for (j, cij) in ci.iter_mut().enumerate() {
+                    *cij = (ai[0] * b[0][j] + ai[1] * b[1][j]) % MOD;
+                }
+            }
+            c
+        }
+    }
+    impl Identity for MatMul {
+        fn id(&self) -> Mat { [[1, 0], [0, 1]] }
+    }
+    impl Associative for MatMul {}
+
+    #[test]
+    fn universal() {
+        let u = (0, 1, 0, 0, 0);
+        let r = (1, 0, 0, 0, 0);
+        let mu = [[1, 1], [0, 1]];
+        let mr = [[2, 0], [3, 5]];
+        for n in 0..15 {
+            for m in 1..15 {
+                for a in 0..15 {
+                    for b in 0..30 {
+                        let ys: Vec<_> =
+                            (0..n).map(|i| (a * i + b) / m).collect();
+                        let (s, q, t) = ys.iter().enumerate().fold(
+                            (0, 0, 0),
+                            |(s, q, t), (i, &y)| {
+                                (s + y, q + y * y, t + i as u64 * y)
+                            },
+                        );
+                        let actual =
+                            universal_euclid(&Sums, n, m, a, b, &u, &r);
+                        assert_eq!(
+                            actual,
+                            (n, ys.last().map_or(0, |&y| y), s, q, t)
+                        );
+
+                        let mut expected = MatMul.id();
+                        let mut prev = 0;
+                        for &y in &ys {
+                            for _ in prev..y {
+                                expected = MatMul.op(&expected, &mu);
+                            }
+                            expected = MatMul.op(&expected, &mr);
+                            prev = y;
+                        }
+                        let actual =
+                            universal_euclid(&MatMul, n, m, a, b, &mu, &mr);
+                        assert_eq!(actual, expected);
+                    }
+                }
+            }
+        }
+
+        let n = 10_u64.pow(9);
+        let m = 12345;
+        let (a, b) = (6789, 1234);
+        let (_, _, s, _, _) = universal_euclid(&Sums, n, m, a, b, &u, &r);
+        let expected = floor_sum(n as i64, m as i64, a as i64, b as i64) as u64;
+        assert_eq!(s, expected);
+    }
+
+    #[test]
+    fn lattice() {
+        for x0 in -5..5 {
+            for x1 in x0..x0 + 8 {
+                for y0 in 0..8 {
+                    for y1 in 0..8 {
+                        let mut expected = 0;
+                        for x in x0..=x1 {
+                            for y in 0..=y0.max(y1) {
+                                // (x, y) is below the segment
+                                let below = x0 == x1
+                                    || (y - y0) * (x1 - x0)
+                                        <= (y1 - y0) * (x - x0);
+                                expected += below as i64;
+                            }
+                        }
+                        let actual =
+                            lattice_points_under_segment((x0, y0), (x1, y1));
+                        assert_eq!(actual, expected);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,576 @@
+use std::ops::{
+    Add, AddAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
+
+use convolution::{butterfly, butterfly_inv, convolve_any, NttFriendly};
+use discrete_log::sqrt_mod;
+use factorial_table::FactorialTable;
+use modint::ModInt;
+
+/// 形式的冪級数。
+///
+/// 係数を昇順に保持する。長さを超える係数は $0$ とみなす。
+///
+/// 乗算と、それを用いる [`log`](Self::log), [`exp`](Self::exp),
+/// [`pow`](Self::pow), [`sqrt`](Self::sqrt), [`div_rem`](Self::div_rem)
+/// などは [`convolve_any`] を経由するので、法が NTT に適していても
+/// 専用の NTT による実装と比べて定数倍が重い。
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Fps<M>(Vec<M>);
+
+impl<M> Default for Fps<M> {
+    fn default() -> Self { Self(vec![]) }
+}
+
+impl<M> From<Vec<M>> for Fps<M> {
+    fn from(buf: Vec<M>) -> Self { Self(buf) }
+}
+
+impl<M: ModInt> Fps<M> {
+    pub fn new() -> Self { Self(vec![]) }
+    pub fn len(&self) -> usize { self.0.len() }
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+    pub fn into_inner(self) -> Vec<M> { self.0 }
+    pub fn coeffs(&self) -> &[M] { &self.0 }
+
+    /// $[x^i] f$。
+    pub fn get(&self, i: usize) -> M {
+        self.0.get(i).copied().unwrap_or(M::new(0))
+    }
+
+    /// $f \\bmod x^n$ を長さ $n$ で返す。
+    pub fn prefix(&self, n: usize) -> Self {
+        let mut res: Vec<_> = self.0.iter().take(n).copied().collect();
+        res.resize(n, M::new(0));
+        Self(res)
+    }
+
+    /// 末尾の $0$ を取り除く。
+    pub fn trim(&mut self) {
+        while self.0.last() == Some(&M::new(0)) {
+            self.0.pop();
+        }
+    }
+
+    /// $f(x)$。
+    pub fn eval(&self, x: M) -> M {
+        self.0.iter().rev().fold(M::new(0), |acc, &a| acc * x + a)
+    }
+
+    pub fn derivative(&self) -> Self {
+        let res = (1..self.len()).map(|i| self.0[i] * M::new(i)).collect();
+        Self(res)
+    }
+
+    pub fn integral(&self) -> Self {
+        let n = self.len();
+        let recip = recips::<M>(n);
+        let res = std::iter::once(M::new(0))
+            .chain((0..n).map(|i| self.0[i] * recip[i + 1]))
+            .collect();
+        Self(res)
+    }
+
+    /// $1/f \\bmod x^n$。$[x^0] f$ が可逆であることを要する。
+    ///
+    /// [`convolve_any`] による Newton 法を用いる。法が [`NttFriendly`]
+    /// であれば [`inv_ntt`](Self::inv_ntt) の方が速い。
+    pub fn inv(&self, n: usize) -> Self {
+        let f0 = self.get(0);
+        assert!(
+            f0.checked_recip().is_some(),
+            "constant term must be invertible"
+        );
+        if n == 0 {
+            return Self::new();
+        }
+        let mut g = vec![f0.recip()];
+        let mut m = 1;
+        while m < n {
+            let m2 = 2 * m;
+            // f g = 1 + x^m h (mod x^{2m})
+            let fg = convolve_any(&self.prefix(m2).0, &g);
+            let h: Vec<_> = (m..m2).map(|i| fg[i]).collect();
+            // g - x^m h g
+            let hg = convolve_any(&h, &g);
+            g.extend(hg[..m].iter().map(|&x| -x));
+            m = m2;
+        }
+        g.truncate(n);
+        Self(g)
+    }
+
+    /// $\\log f \\bmod x^n$。$[x^0] f = 1$ を要する。
+    ///
+    /// [`inv`](Self::inv) と [`convolve_any`] による乗算で求める。
+    pub fn log(&self, n: usize) -> Self {
+        assert!(self.get(0) == M::new(1), "constant term must be one");
+        if n == 0 {
+            return Self::new();
+        }
+        let df = self.prefix(n).derivative();
+        (df * self.inv(n)).prefix(n - 1).integral()
+    }
+
+    /// $\\exp f \\bmod x^n$。$[x^0] f = 0$ を要する。
+    ///
+    /// [`log`](Self::log) を用いる Newton 法で求める。
+    pub fn exp(&self, n: usize) -> Self {
+        assert!(self.get(0) == M::new(0), "constant term must be zero");
+        let mut g = Self(vec![M::new(1)]);
+        let mut m = 1;
+        while m < n {
+            m *= 2;
+            let mut h = self.prefix(m) - g.log(m);
+            h[0] += M::new(1);
+            g = (g * h).prefix(m);
+        }
+        g.prefix(n)
+    }
+
+    /// $f^k \\bmod x^n$。
+    pub fn pow(&self, k: u64, n: usize) -> Self {
+        if k == 0 {
+            return Self(vec![M::new(1)]).prefix(n);
+        }
+        let Some(t) = self.0.iter().position(|&x| x != M::new(0)) else {
+            return self.prefix(n);
+        };
+        let shift = match (t as u64).checked_mul(k) {
+            Some(shift) if shift < n as u64 => shift as usize,
+            _ => return Self::new().prefix(n),
+        };
+        let c = self.0[t];
+        let ic = c.recip();
+        let g: Vec<_> = self.0[t..].iter().map(|&x| x * ic).collect();
+        let logk = Self(g).log(n - shift) * M::new(k);
+        let ck = c.pow(k);
+        let res = std::iter::repeat_n(M::new(0), shift)
+            .chain(logk.exp(n - shift).0.into_iter().map(|x| x * ck))
+            .collect();
+        Self(res)
+    }
+
+    /// $\\sqrt{f} \\bmod x^n$ のひとつ。存在しなければ `None`。
+    pub fn sqrt(&self, n: usize) -> Option<Self> {
+        // terms of degree n or more do not affect the result
+        let nonzero = self.0.iter().take(n).position(|&x| x != M::new(0));
+        let Some(t) = nonzero else {
+            return Some(Self::new().prefix(n));
+        };
+        if t % 2 != 0 {
+            return None;
+        }
+        let shift = t / 2;
+        let f = Self(self.0[t..].to_vec());
+        let len = n - shift;
+        let mut g = Self(vec![sqrt_mod(f.0[0])?]);
+        let half = M::new(2).recip();
+        let mut m = 1;
+        while m < len {
+            m *= 2;
+            g = (f.prefix(m) * g.inv(m) + g).prefix(m) * half;
+        }
+        let res = std::iter::repeat_n(M::new(0), shift)
+            .chain(g.0.into_iter().take(len))
+            .collect();
+        Some(Self(res))
+    }
+
+    /// 多項式としての商と余り。
+    ///
+    /// 余りの長さは `rhs` の次数未満に切り詰める。`rhs` が $0$ なら panic する。
+    pub fn div_rem(&self, rhs: &Self) -> (Self, Self) {
+        let mut a = self.clone();
+        let mut b = rhs.clone();
+        a.trim();
+        b.trim();
+        assert!(!b.is_empty(), "division by zero");
+        let (n, m) = (a.len(), b.len());
+        if n < m {
+            return (Self::new(), a);
+        }
+        let k = n - m + 1;
+        let ra = Self(a.0.iter().rev().take(k).copied().collect());
+        let rb = Self(b.0.iter().rev().copied().collect());
+        let mut q = (ra * rb.inv(k)).prefix(k);
+        q.0.reverse();
+        let mut r = a - &b * &q;
+        r.0.truncate(m - 1);
+        r.trim();
+        (q, r)
+    }
+
+    /// $f(x + c)$。
+    pub fn taylor_shift(&self, c: M) -> Self {
+        let n = self.len();
+        if n == 0 {
+            return Self::new();
+        }
+        let ft = FactorialTable::<M>::new(n);
+        let a: Vec<_> =
+            (0..n).rev().map(|i| self.0[i] * ft.factorial(i)).collect();
+        let mut pow = M::new(1);
+        let b: Vec<_> = (0..n)
+            .map(|i| {
+                let tmp = pow * ft.factorial_recip(i);
+                pow *= c;
+                tmp
+            })
+            .collect();
+        let ab = convolve_any(&a, &b);
+        let res =
+            (0..n).map(|i| ab[n - 1 - i] * ft.factorial_recip(i)).collect();
+        Self(res)
+    }
+}
+
+impl<M: NttFriendly + 'static> Fps<M> {
+    /// $1/f \\bmod x^n$。$[x^0] f$ が可逆であることを要する。
+    ///
+    /// [`inv`](Self::inv) と同じ値を、NTT による Newton 法で求める。
+    pub fn inv_ntt(&self, n: usize) -> Self {
+        let f0 = self.get(0);
+        assert!(
+            f0.checked_recip().is_some(),
+            "constant term must be invertible"
+        );
+        assert!(n <= 1 << M::EXP, "length exceeds the NTT size");
+        let mut g = vec![f0.recip()];
+        let mut m = 1;
+        while m < n {
+            let m2 = 2 * m;
+            let iz = M::new(m2).recip();
+            let mut gb = g.clone();
+            gb.resize(m2, M::new(0));
+            butterfly(&mut gb);
+
+            // f g = 1 + x^m h (mod x^{2m})
+            let mut fb = self.prefix(m2).0;
+            butterfly(&mut fb);
+            for (fi, gi) in fb.iter_mut().zip(&gb) {
+                *fi *= *gi;
+            }
+            butterfly_inv(&mut fb);
+            for fi in &mut fb[..m] {
+                *fi = M::new(0);
+            }
+            for fi in &mut fb[m..] {
+                *fi *= iz;
+            }
+
+            // g - x^m h g
+            butterfly(&mut fb);
+            for (fi, gi) in fb.iter_mut().zip(&gb) {
+                *fi *= *gi;
+            }
+            butterfly_inv(&mut fb);
+            g.extend(fb[m..].iter().map(|&x| -(x * iz)));
+            m = m2;
+        }
+        g.truncate(n);
+        Self(g)
+    }
+}
+
+// 1/i for i in 0..=n, with 1/0 replaced by 0
+fn recips<M: ModInt>(n: usize) -> Vec<M> {
+    let m = M::modulus_u64();
+    let mut res = vec![M::new(0); n + 1];
+    if n >= 1 {
+        res[1] = M::new(1);
+    }
+    for i in 2..=n {
+        let i64 = i as u64;
+        res[i] = -(res[(m % i64) as usize] * M::new(m / i64));
+    }
+    res
+}
+
+impl<M> Index<usize> for Fps<M> {
+    type Output = M;
+    fn index(&self, i: usize) -> &M { &self.0[i] }
+}
+
+impl<M> IndexMut<usize> for Fps<M> {
+    fn index_mut(&mut self, i: usize) -> &mut M { &mut self.0[i] }
+}
+
+impl<M: ModInt> AddAssign<&Fps<M>> for Fps<M> {
+    fn add_assign(&mut self, rhs: &Fps<M>) {
+        if self.len() < rhs.len() {
+            self.0.resize(rhs.len(), M::new(0));
+        }
+        for (x, &y) in self.0.iter_mut().zip(&rhs.0) {
+            *x += y;
+        }
+    }
+}
+
+impl<M: ModInt> SubAssign<&Fps<M>> for Fps<M> {
+    fn sub_assign(&mut self, rhs: &Fps<M>) {
+        if self.len() < rhs.len() {
+            self.0.resize(rhs.len(), M::new(0));
+        }
+        for (x, &y) in self.0.iter_mut().zip(&rhs.0) {
+            *x -= y;
+        }
+    }
+}
+
+impl<M: ModInt> MulAssign<&Fps<M>> for Fps<M> {
+    fn mul_assign(&mut self, rhs: &Fps<M>) {
+        let lhs = std::mem::take(&mut self.0);
+        self.0 = convolve_any(&lhs, &rhs.0);
+    }
+}
+
+impl<M: ModInt> MulAssign<M> for Fps<M> {
+    fn mul_assign(&mut self, rhs: M) {
+        for x in &mut self.0 {
+            *x *= rhs;
+        }
+    }
+}
+
+macro_rules! impl_bin_op {
+    ( $( ($op:ident, $op_trait:ident, $op_assign:ident, $op_assign_trait:ident), )* ) => { $(
+        impl<M: ModInt> $op_assign_trait for Fps<M> {
+            fn $op_assign(&mut self, rhs: Fps<M>) { self.$op_assign(&rhs) }
+        }
+        impl<M: ModInt> $op_trait for Fps<M> {
+            type Output = Fps<M>;
+            fn $op(mut self, rhs: Fps<M>) -> Fps<M> {
+                self.$op_assign(&rhs);
+                self
+            }
+        }
+        impl<M: ModInt> $op_trait<&Fps<M>> for Fps<M> {
+            type Output = Fps<M>;
+            fn $op(mut self, rhs: &Fps<M>) -> Fps<M> {
+                self.$op_assign(rhs);
+                self
+            }
+        }
+        impl<M: ModInt> $op_trait<Fps<M>> for &Fps<M> {
+            type Output = Fps<M>;
+            fn $op(self, rhs: Fps<M>) -> Fps<M> {
+                let mut tmp = self.clone();
+                tmp.$op_assign(&rhs);
+                tmp
+            }
+        }
+        impl<M: ModInt> $op_trait<&Fps<M>> for &Fps<M> {
+            type Output = Fps<M>;
+            fn $op(self, rhs: &Fps<M>) -> Fps<M> {
+                let mut tmp = self.clone();
+                tmp.$op_assign(rhs);
+                tmp
+            }
+        }
+    )* }
+}
+
+impl_bin_op! {
+    ( add, Add, add_assign, AddAssign ),
+    ( sub, Sub, sub_assign, SubAssign ),
+    ( mul, Mul, mul_assign, MulAssign ),
+}
+
+impl<M: ModInt> Mul<M> for Fps<M> {
+    type Output = Fps<M>;
+    fn mul(mut self, rhs: M) -> Fps<M> {
+        self *= rhs;
+        self
+    }
+}
+
+impl<M: ModInt> Neg for Fps<M> {
+    type Output = Fps<M>;
+    fn neg(mut self) -> Fps<M> {
+        for x in &mut self.0 {
+            *x = -*x;
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use modint::ModInt998244353;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    type Mi = ModInt998244353;
+
+    fn random_fps(len: usize, rng: &mut ChaCha20Rng) -> Fps<Mi> {
+        let res = (0..len).map(|_| Mi::new(rng.gen::<u32>())).collect();
+        Fps(res)
+    }
+
+    fn mul_naive(a: &Fps<Mi>, b: &Fps<Mi>, n: usize) -> Fps<Mi> {
+        let mut res = vec![Mi::new(0); n];
+        for i in 0..a.len().min(n) {
+            for j in 0..b.len().min(n - i) {
+                res[i + j] += a[i] * b[j];
+            }
+        }
+        Fps::from(res)
+    }
+
+    fn inv_naive(f: &Fps<Mi>, n: usize) -> Fps<Mi> {
+        let f0 = f[0].recip();
+        let mut g = vec![Mi::new(0); n];
+        for i in 0..n {
+            let sum: Mi = (1..=i).map(|j| f.get(j) * g[i - j]).sum();
+            g[i] = if i == 0 { f0 } else { -sum * f0 };
+        }
+        Fps::from(g)
+    }
+
+    fn exp_naive(f: &Fps<Mi>, n: usize) -> Fps<Mi> {
+        // n g[n] = sum_{k=1}^n k f[k] g[n-k]
+        let mut g = vec![Mi::new(0); n];
+        if n > 0 {
+            g[0] = Mi::new(1);
+        }
+        for i in 1..n {
+            let sum: Mi =
+                (1..=i).map(|k| Mi::new(k) * f.get(k) * g[i - k]).sum();
+            g[i] = sum / Mi::new(i);
+        }
+        Fps::from(g)
+    }
+
+    #[test]
+    fn arithmetic() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        for (n, m) in [(0, 3), (5, 3), (100, 80)] {
+            let a = random_fps(n, &mut rng);
+            let b = random_fps(m, &mut rng);
+            let len = if n == 0 || m == 0 { 0 } else { n + m - 1 };
+            assert_eq!(&a * &b, mul_naive(&a, &b, len));
+            let sum = &a + &b;
+            let diff = &a - &b;
+            for i in 0..n.max(m) {
+                assert_eq!(sum[i], a.get(i) + b.get(i));
+                assert_eq!(diff[i], a.get(i) - b.get(i));
+            }
+            assert_eq!(a.derivative().integral().prefix(n), {
+                let mut tmp = a.clone();
+                if n > 0 {
+                    tmp[0] = Mi::new(0);
+                }
+                tmp
+            });
+        }
+    }
+
+    #[test]
+    fn inv_log_exp() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        for n in [0, 1, 2, 3, 10, 64, 100, 300] {
+            let mut f = random_fps(n.max(1), &mut rng);
+            f[0] = Mi::new(1);
+            let g = f.inv(n);
+            assert_eq!(g, inv_naive(&f, n));
+            assert_eq!(f.inv_ntt(n), g);
+
+            let log = f.log(n);
+            assert_eq!(log.len(), n);
+            // (log f)' f = f'
+            if n > 0 {
+                let lhs = mul_naive(&log.derivative(), &f, n - 1);
+                assert_eq!(lhs, f.derivative().prefix(n - 1));
+            }
+
+            f[0] = Mi::new(0);
+            let exp = f.exp(n);
+            assert_eq!(exp, exp_naive(&f, n));
+        }
+    }
+
+    #[test]
+    fn pow_sqrt() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        for n in [0, 1, 5, 40] {
+            for t in [0, 1, 2, 7] {
+                let mut f = random_fps(10, &mut rng);
+                for i in 0..t.min(10) {
+                    f[i] = Mi::new(0);
+                }
+                for k in [0, 1, 2, 5] {
+                    let mut expected = Fps::from(vec![Mi::new(1)]).prefix(n);
+                    for _ in 0..k {
+                        expected = mul_naive(&expected, &f, n);
+                    }
+                    assert_eq!(f.pow(k, n), expected);
+                }
+                if t > 0 {
+                    assert_eq!(f.pow(1 << 62, n), Fps::new().prefix(n));
+                }
+
+                let sq = mul_naive(&f, &f, n);
+                let g = sq.sqrt(n).unwrap();
+                assert_eq!(mul_naive(&g, &g, n), sq);
+            }
+        }
+        let f = Fps::from(vec![Mi::new(0), Mi::new(1)]);
+        assert_eq!(f.sqrt(5), None);
+        let f = Fps::from(vec![Mi::new(0), Mi::new(0), Mi::new(0), Mi::new(1)]);
+        assert_eq!(f.sqrt(2), Some(Fps::new().prefix(2)));
+        assert_eq!(f.sqrt(4), None);
+        // 3 is a quadratic non-residue mod 998244353
+        let f = Fps::from(vec![Mi::new(3), Mi::new(1)]);
+        assert_eq!(f.sqrt(5), None);
+    }
+
+    #[test]
+    fn div_rem() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        for (n, m) in [(0, 1), (3, 5), (10, 1), (100, 37), (50, 50)] {
+            let a = random_fps(n, &mut rng);
+            let b = random_fps(m, &mut rng);
+            let (q, r) = a.div_rem(&b);
+            assert!(r.len() < m);
+            let mut lhs = &b * &q + &r;
+            lhs.trim();
+            let mut a = a;
+            a.trim();
+            assert_eq!(lhs, a);
+        }
+    }
+
+    #[test]
+    fn taylor_shift() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let f = random_fps(30, &mut rng);
+        let c = Mi::new(12345);
+        let g = f.taylor_shift(c);
+        // f(x + c) at x = y equals f(y + c)
+        for y in 0..40 {
+            let y = Mi::new(y);
+            assert_eq!(g.eval(y), f.eval(y + c));
+        }
+    }
+
+    #[test]
+    fn arbitrary_modulus() {
+        type Mi = modint::ModInt1000000007;
+
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let f: Vec<_> = (0..300).map(|_| Mi::new(rng.gen::<u32>())).collect();
+        let mut f = Fps::from(f);
+        f[0] = Mi::new(1);
+        let n = f.len();
+        let one = Fps::from(vec![Mi::new(1)]).prefix(n);
+        assert_eq!((&f * &f.inv(n)).prefix(n), one);
+        assert_eq!(f.log(n).exp(n), f);
+        let sq = (&f * &f).prefix(n);
+        let g = sq.sqrt(n).unwrap();
+        assert_eq!((&g * &g).prefix(n), sq);
+        assert_eq!(f.pow(3, n), (&(&f * &f) * &f).prefix(n));
+    }
+}
@@ -0,0 +1,144 @@
+use convolution::convolve_any;
+use modint::ModInt;
+
+/// Berlekamp–Massey。
+///
+/// $a_i = \\sum_{j=1}^d c_j a_{i-j}$ ($d\\le i\\lt n$) を満たす最短の
+/// $(c_1, \\dots, c_d)$ を $O(n^2)$ 時間で返す。
+pub fn berlekamp_massey<M: ModInt>(a: &[M]) -> Vec<M> {
+    let zero = M::new(0);
+    // a_i - sum_j c_j a_{i-j} = 0 where c = cur[1..]
+    let mut cur = vec![M::new(1)];
+    let mut prev = vec![M::new(1)];
+    let mut prev_d = M::new(1);
+    let mut shift = 1;
+    for i in 0..a.len() {
+        let d: M = (0..cur.len()).map(|j| cur[j] * a[i - j]).sum();
+        if d == zero {
+            shift += 1;
+            continue;
+        }
+        let coef = d / prev_d;
+        let tmp = cur.clone();
+        let len = (prev.len() + shift).max(cur.len());
+        cur.resize(len, zero);
+        for (j, &pj) in prev.iter().enumerate() {
+            cur[j + shift] -= coef * pj;
+        }
+        if 2 * (tmp.len() - 1) <= i {
+            prev = tmp;
+            prev_d = d;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+    }
+    cur[1..].iter().map(|&c| -c).collect()
+}
+
+/// 線形漸化式の第 $n$ 項。
+///
+/// $a_i = \\sum_{j=1}^d c_j a_{i-j}$ ($i\\ge d$) で定まる数列の $a_n$ を、
+/// Bostan–Mori により $O(d\\log(d)\\log(n))$ 時間で返す。`coeffs` は
+/// $(c_1, \\dots, c_d)$、`init` は $(a_0, \\dots, a_{d-1})$ を含む。
+pub fn nth_term_of_linear_recurrence<M: ModInt>(
+    coeffs: &[M],
+    init: &[M],
+    n: u64,
+) -> M {
+    let d = coeffs.len();
+    assert!(init.len() >= d);
+    if n < init.len() as u64 {
+        return init[n as usize];
+    }
+    if d == 0 {
+        return M::new(0);
+    }
+    let q: Vec<_> =
+        std::iter::once(M::new(1)).chain(coeffs.iter().map(|&c| -c)).collect();
+    let mut p = convolve_any(&init[..d], &q);
+    p.truncate(d);
+    bostan_mori(p, q, n)
+}
+
+// [x^n] p/q
+fn bostan_mori<M: ModInt>(mut p: Vec<M>, mut q: Vec<M>, mut n: u64) -> M {
+    while n > 0 {
+        let q_neg: Vec<_> = q
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| if i % 2 == 0 { x } else { -x })
+            .collect();
+        let u = convolve_any(&p, &q_neg);
+        let v = convolve_any(&q, &q_neg);
+        let parity = (n % 2) as usize;
+        p = u.into_iter().skip(parity).step_by(2).collect();
+        q = v.into_iter().step_by(2).collect();
+        n /= 2;
+    }
+    p.first().copied().unwrap_or(M::new(0)) / q[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use modint::ModInt998244353;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    type Mi = ModInt998244353;
+
+    fn extend(coeffs: &[Mi], init: &[Mi], n: usize) -> Vec<Mi> {
+        let d = coeffs.len();
+        let mut a = init.to_vec();
+        while a.len() < n {
+            let i = a.len();
+            let next = (1..=d).map(|j| coeffs[j - 1] * a[i - j]).sum();
+            a.push(next);
+        }
+        a
+    }
+
+    #[test]
+    fn fibonacci() {
+        let one = Mi::new(1);
+        let coeffs = [one, one];
+        let init = [Mi::new(0), one];
+        let a = extend(&coeffs, &init, 100);
+        for n in 0..100 {
+            let actual = nth_term_of_linear_recurrence(&coeffs, &init, n);
+            assert_eq!(actual, a[n as usize]);
+        }
+        assert_eq!(berlekamp_massey(&a), coeffs);
+        // F(10^18) mod 998244353
+        let actual =
+            nth_term_of_linear_recurrence(&coeffs, &init, 10_u64.pow(18));
+        assert_eq!(actual, Mi::new(23849548));
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut rand = || Mi::new(rng.gen::<u32>());
+        for d in [0, 1, 2, 5, 30, 100] {
+            let coeffs: Vec<_> = (0..d).map(|_| rand()).collect();
+            let init: Vec<_> = (0..d).map(|_| rand()).collect();
+            let n = 2 * d + 50;
+            let a = extend(&coeffs, &init, n);
+            for (i, &ai) in a.iter().enumerate() {
+                let actual =
+                    nth_term_of_linear_recurrence(&coeffs, &init, i as u64);
+                assert_eq!(actual, ai);
+            }
+            let found = berlekamp_massey(&a);
+            assert!(found.len() <= d);
+            assert_eq!(extend(&found, &a[..found.len()], n), a);
+        }
+        assert!(berlekamp_massey::<Mi>(&[]).is_empty());
+        assert_eq!(
+            berlekamp_massey(&[Mi::new(0), Mi::new(0), Mi::new(1)]).len(),
+            3
+        );
+    }
+}
@@ -31,6 +31,7 @@ impl LinearSieve {
         Self { lpf, lpf_e, pr }
     }
 
+    pub fn limit(&self) -> usize { self.lpf.len() - 1 }
     pub fn is_prime(&self, i: usize) -> bool { i >= 2 && self.lpf[i] == i }
     pub fn lpf(&self, i: usize) -> Option<usize> {
         (i >= 2).then(|| self.lpf[i])
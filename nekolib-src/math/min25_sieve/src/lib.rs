@@ -0,0 +1,200 @@
+use linear_sieve::LinearSieve;
+use modint::ModInt;
+use multipoint::interpolate_iota;
+
+/// $\\lfloor n/i\\rfloor$ の形の値を添字づける。
+struct QuotientIndex {
+    n: u64,
+    sqrt: u64,
+    // n/1, n/2, ..., 2, 1 (distinct, descending)
+    values: Vec<u64>,
+}
+
+impl QuotientIndex {
+    fn new(n: u64) -> Self {
+        let sqrt = n.isqrt();
+        let mut values = vec![];
+        let mut i = 1;
+        while i <= n {
+            let v = n / i;
+            values.push(v);
+            i = n / v + 1;
+        }
+        Self { n, sqrt, values }
+    }
+    fn index(&self, v: u64) -> usize {
+        if v <= self.sqrt {
+            self.values.len() - v as usize
+        } else {
+            (self.n / v) as usize - 1
+        }
+    }
+}
+
+// 完全乗法的な w について、g[i] = sum_{2 <= k <= values[i]} w(k) を
+// g[i] = sum_{p <= values[i]} w(p) に書き換える
+fn lucy<T: Copy>(
+    qi: &QuotientIndex,
+    primes: &[u64],
+    g: &mut [T],
+    w: impl Fn(u64) -> T,
+    sub_mul: impl Fn(T, T, T, T) -> T,
+) {
+    for &p in primes {
+        let wp = w(p);
+        let prev = g[qi.index(p - 1)];
+        for i in 0..qi.values.len() {
+            let v = qi.values[i];
+            if v < p * p {
+                break;
+            }
+            let gvp = g[qi.index(v / p)];
+            g[i] = sub_mul(g[i], wp, gvp, prev);
+        }
+    }
+}
+
+/// $n$ 以下の素数の個数。$O(n^{3/4}/\\log(n))$ 時間。
+pub fn prime_count(n: u64) -> u64 {
+    if n < 2 {
+        return 0;
+    }
+    let qi = QuotientIndex::new(n);
+    let primes: Vec<_> =
+        LinearSieve::new(qi.sqrt as usize).primes().map(|p| p as u64).collect();
+    let mut g: Vec<_> = qi.values.iter().map(|&v| v - 1).collect();
+    // g(v) -= g(v/p) - g(p-1)
+    lucy(&qi, &primes, &mut g, |_| 0, |gv, _, gvp, prev| gv - (gvp - prev));
+    g[0]
+}
+
+/// 乗法的関数の prefix sum $\\sum_{i=1}^n f(i)$。
+///
+/// 素数 $p$ に対して $f(p) = \\sum_k c_k p^k$ であり、`fp` は
+/// $(c_0, c_1, \\dots)$ を、`fpe(p, e)` は $f(p^e)$ を表す。min_25 篩により
+/// $O(n^{3/4}/\\log(n))$ 時間程度で求める。
+pub fn multiplicative_sum<M: ModInt>(
+    n: u64,
+    fp: &[M],
+    fpe: impl Fn(u64, u32) -> M,
+) -> M {
+    if n == 0 {
+        return M::new(0);
+    }
+    let qi = QuotientIndex::new(n);
+    let primes: Vec<_> =
+        LinearSieve::new(qi.sqrt as usize).primes().map(|p| p as u64).collect();
+
+    // gp[i] = sum_{p <= values[i]} f(p)
+    let mut gp = vec![M::new(0); qi.values.len()];
+    for (k, &ck) in fp.iter().enumerate() {
+        // sum_{i=1}^x i^k for x = 0, 1, ..., k+1
+        let ys: Vec<_> = std::iter::once(M::new(0))
+            .chain((1..=k + 1).scan(M::new(0), |acc, i| {
+                *acc += M::new(i).pow(k as u32);
+                Some(*acc)
+            }))
+            .collect();
+        let mut g: Vec<_> = qi
+            .values
+            .iter()
+            .map(|&v| interpolate_iota(&ys, M::new(v)) - M::new(1))
+            .collect();
+        let w = |p: u64| M::new(p).pow(k as u32);
+        lucy(&qi, &primes, &mut g, w, |gv, wp, gvp, prev| {
+            gv - wp * (gvp - prev)
+        });
+        for (gpi, gi) in gp.iter_mut().zip(g) {
+            *gpi += ck * gi;
+        }
+    }
+
+    // prefix sums of f(p) over the sieved primes
+    let fp_sum: Vec<_> = std::iter::once(M::new(0))
+        .chain(primes.iter().scan(M::new(0), |acc, &p| {
+            *acc += fpe(p, 1);
+            Some(*acc)
+        }))
+        .collect();
+
+    // sum of f(i) for 2 <= i <= x, with lpf(i) >= primes[j]
+    fn rec<M: ModInt>(
+        x: u64,
+        j: usize,
+        qi: &QuotientIndex,
+        primes: &[u64],
+        gp: &[M],
+        fp_sum: &[M],
+        fpe: &impl Fn(u64, u32) -> M,
+    ) -> M {
+        let mut res = gp[qi.index(x)] - fp_sum[j];
+        for (k, &p) in primes.iter().enumerate().skip(j) {
+            if p * p > x {
+                break;
+            }
+            let mut pe = p;
+            let mut e = 1;
+            while pe * p <= x {
+                let sub = rec(x / pe, k + 1, qi, primes, gp, fp_sum, fpe);
+                res += fpe(p, e) * sub + fpe(p, e + 1);
+                pe *= p;
+                e += 1;
+            }
+        }
+        res
+    }
+
+    rec(n, 0, &qi, &primes, &gp, &fp_sum, &fpe) + M::new(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use modint::ModInt998244353;
+
+    use crate::*;
+
+    type Mi = ModInt998244353;
+
+    #[test]
+    fn count() {
+        let n_max = 3000;
+        let ls = LinearSieve::new(n_max);
+        let mut count = 0;
+        for n in 0..=n_max {
+            if ls.is_prime(n) {
+                count += 1;
+            }
+            assert_eq!(prime_count(n as u64), count);
+        }
+        assert_eq!(prime_count(10_u64.pow(9)), 50847534);
+        assert_eq!(prime_count(10_u64.pow(10)), 455052511);
+    }
+
+    #[test]
+    fn multiplicative() {
+        let n_max = 2000;
+        let ls = LinearSieve::new(n_max);
+        let phi = |p: u64, e: u32| Mi::new(p.pow(e) - p.pow(e - 1));
+        let sigma = |p: u64, e: u32| Mi::new((p.pow(e + 1) - 1) / (p - 1));
+        let mu = |_: u64, e: u32| if e == 1 { -Mi::new(1) } else { Mi::new(0) };
+        let mut sum_phi = Mi::new(0);
+        let mut sum_sigma = Mi::new(0);
+        let mut sum_mu = Mi::new(0);
+        for n in 1..=n_max {
+            sum_phi += Mi::new(ls.euler_phi(n));
+            sum_sigma += Mi::new(ls.divisors_sum(n));
+            sum_mu += ls.factors(n).map(|(_, e)| mu(0, e)).product::<Mi>();
+            let n64 = n as u64;
+            let one = Mi::new(1);
+            assert_eq!(multiplicative_sum(n64, &[-one, one], phi), sum_phi);
+            assert_eq!(multiplicative_sum(n64, &[one, one], sigma), sum_sigma);
+            assert_eq!(multiplicative_sum(n64, &[-one], mu), sum_mu);
+        }
+        assert_eq!(multiplicative_sum(0, &[Mi::new(1)], phi), Mi::new(0));
+
+        // Mertens function
+        let n = 10_u64.pow(9);
+        let actual = multiplicative_sum(n, &[-Mi::new(1)], mu);
+        assert_eq!(actual, -Mi::new(222));
+    }
+}
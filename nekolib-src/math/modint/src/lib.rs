@@ -2,9 +2,11 @@ use std::{
     fmt,
     hash::{Hash, Hasher},
     iter::{Product, Sum},
+    marker::PhantomData,
     ops::{
         Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign,
     },
+    sync::OnceLock,
 };
 
 use bin_iter::BinIter;
@@ -17,6 +19,7 @@ impl<const MOD: u32> StaticModInt<MOD> {
     pub fn new(val: impl RemEuclidU32) -> Self { Self::from(val) }
     pub const fn new_const(val: u32) -> Self { Self(val.rem_euclid(MOD)) }
     pub fn modulus() -> u32 { MOD }
+    pub fn get(self) -> u32 { self.0 }
 }
 
 impl<const MOD: u32> Default for StaticModInt<MOD> {
@@ -48,105 +51,470 @@ impl<const MOD: u32> MulAssign for StaticModInt<MOD> {
     }
 }
 
-impl<const MOD: u32> DivAssign for StaticModInt<MOD> {
-    fn div_assign(&mut self, rhs: Self) { *self *= rhs.recip() }
-}
-
 impl<const MOD: u32> StaticModInt<MOD> {
     pub fn recip(self) -> Self { self.checked_recip().unwrap() }
     pub fn checked_recip(self) -> Option<Self> {
         let (g, r) = (self.0 as u64).gcd_recip(MOD as u64);
         (g == 1).then_some(Self::new(r))
     }
-    pub fn pow(self, exp: impl BinIter) -> Self {
-        let mut res = Self::new(1);
-        let mut dbl = self;
-        for b in exp.bin_iter() {
-            if b {
-                res *= dbl;
-            }
-            dbl *= dbl;
+}
+
+impl<const MOD: u32, I: RemEuclidU32> From<I> for StaticModInt<MOD> {
+    fn from(val: I) -> Self { Self(val.rem_euclid_u32(MOD)) }
+}
+
+impl<const MOD: u32> Hash for StaticModInt<MOD> {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.0.hash(state) }
+}
+
+/// 法を実行時に決める [`DynamicModInt`] の、法を保持する型。
+///
+/// 法は [`OnceLock`] に一度だけ設定され、同じ `Id` に対して後から変更することはできない。
+/// 異なる法を使うときは、それぞれに別の `Id` を用意する。
+///
+/// ```
+/// use std::sync::OnceLock;
+///
+/// use modint::{Barrett, DynamicModInt, DynamicModIntId};
+///
+/// enum Id {}
+/// impl DynamicModIntId for Id {
+///     fn barrett() -> &'static OnceLock<Barrett> {
+///         static BARRETT: OnceLock<Barrett> = OnceLock::new();
+///         &BARRETT
+///     }
+/// }
+/// type Mi = DynamicModInt<Id>;
+///
+/// Mi::set_modulus(10);
+/// assert_eq!(Mi::new(7) * Mi::new(8), Mi::new(6));
+/// ```
+pub trait DynamicModIntId: 'static {
+    fn barrett() -> &'static OnceLock<Barrett>;
+}
+
+/// Barrett reduction。
+#[derive(Clone, Copy, Debug)]
+pub struct Barrett {
+    m: u32,
+    // ceil(2^64 / m), or 0 for m = 1
+    im: u64,
+}
+
+impl Barrett {
+    pub fn new(m: u32) -> Self {
+        assert!(m >= 1);
+        Self { m, im: (u64::MAX / m as u64).wrapping_add(1) }
+    }
+    pub fn modulus(&self) -> u32 { self.m }
+    /// $a\\cdot b \\bmod m$。ただし $a, b \\lt m$ とする。
+    pub fn mul(&self, a: u32, b: u32) -> u32 {
+        let z = a as u64 * b as u64;
+        let x = ((z as u128 * self.im as u128) >> 64) as u64;
+        let y = x.wrapping_mul(self.m as u64);
+        let res = z.wrapping_sub(y);
+        (if z < y { res.wrapping_add(self.m as u64) } else { res }) as u32
+    }
+}
+
+pub struct DynamicModInt<Id>(u32, PhantomData<fn() -> Id>);
+
+impl<Id: DynamicModIntId> DynamicModInt<Id> {
+    pub fn new(val: impl RemEuclidU32) -> Self { Self::from(val) }
+    /// 法を設定する。すでに異なる法が設定されていれば panic する。
+    pub fn set_modulus(m: u32) {
+        let barrett = Id::barrett().get_or_init(|| Barrett::new(m));
+        assert_eq!(barrett.modulus(), m, "modulus is already set");
+    }
+    /// 法を返す。法が設定されていなければ panic する。
+    pub fn modulus() -> u32 { Self::barrett().modulus() }
+    pub fn get(self) -> u32 { self.0 }
+    fn barrett() -> &'static Barrett {
+        Id::barrett().get().expect("modulus is not set")
+    }
+}
+
+impl<Id> Clone for DynamicModInt<Id> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<Id> Copy for DynamicModInt<Id> {}
+
+impl<Id> PartialEq for DynamicModInt<Id> {
+    fn eq(&self, other: &Self) -> bool { self.0 == other.0 }
+}
+
+impl<Id> Eq for DynamicModInt<Id> {}
+
+impl<Id> Default for DynamicModInt<Id> {
+    fn default() -> Self { Self(0, PhantomData) }
+}
+
+impl<Id: DynamicModIntId> AddAssign for DynamicModInt<Id> {
+    fn add_assign(&mut self, rhs: Self) {
+        let m = Self::modulus();
+        let (tmp, carry) = self.0.overflowing_add(rhs.0);
+        self.0 = if carry || tmp >= m { tmp.wrapping_sub(m) } else { tmp };
+    }
+}
+
+impl<Id: DynamicModIntId> SubAssign for DynamicModInt<Id> {
+    fn sub_assign(&mut self, rhs: Self) {
+        if self.0 < rhs.0 {
+            self.0 = self.0.wrapping_add(Self::modulus());
         }
-        res
+        self.0 = self.0.wrapping_sub(rhs.0);
+    }
+}
+
+impl<Id: DynamicModIntId> MulAssign for DynamicModInt<Id> {
+    fn mul_assign(&mut self, rhs: Self) {
+        self.0 = Self::barrett().mul(self.0, rhs.0);
+    }
+}
+
+impl<Id: DynamicModIntId> DynamicModInt<Id> {
+    pub fn recip(self) -> Self { self.checked_recip().unwrap() }
+    pub fn checked_recip(self) -> Option<Self> {
+        let (g, r) = (self.0 as u64).gcd_recip(Self::modulus() as u64);
+        (g == 1).then_some(Self::new(r))
+    }
+}
+
+impl<Id: DynamicModIntId, I: RemEuclidU32> From<I> for DynamicModInt<Id> {
+    fn from(val: I) -> Self {
+        Self(val.rem_euclid_u32(Self::modulus()), PhantomData)
+    }
+}
+
+impl<Id> Hash for DynamicModInt<Id> {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.0.hash(state) }
+}
+
+/// 奇数の法を実行時に決める [`MontgomeryModInt`] の、法を保持する型。
+///
+/// 値は $2^{32}$ 以上になりうるので、[`ModInt`] を通して読むときは
+/// [`ModInt::get`] ではなく [`ModInt::get_u64`] を使う。
+///
+/// ```
+/// use std::sync::OnceLock;
+///
+/// use modint::{Montgomery, MontgomeryModInt, MontgomeryModIntId};
+///
+/// enum Id {}
+/// impl MontgomeryModIntId for Id {
+///     fn montgomery() -> &'static OnceLock<Montgomery> {
+///         static MONTGOMERY: OnceLock<Montgomery> = OnceLock::new();
+///         &MONTGOMERY
+///     }
+/// }
+/// type Mi = MontgomeryModInt<Id>;
+///
+/// let m = (1 << 61) - 1;
+/// Mi::set_modulus(m);
+/// assert_eq!(Mi::new(m - 1) * Mi::new(m - 1), Mi::new(1));
+/// ```
+pub trait MontgomeryModIntId: 'static {
+    fn montgomery() -> &'static OnceLock<Montgomery>;
+}
+
+/// Montgomery reduction。
+#[derive(Clone, Copy, Debug)]
+pub struct Montgomery {
+    m: u64,
+    // m^{-1} mod 2^64
+    minv: u64,
+    // 2^128 mod m
+    r2: u64,
+}
+
+impl Montgomery {
+    pub fn new(m: u64) -> Self {
+        assert!(m % 2 == 1, "modulus must be odd");
+        let mut minv = m;
+        for _ in 0..5 {
+            minv = minv.wrapping_mul(2_u64.wrapping_sub(m.wrapping_mul(minv)));
+        }
+        let r = (u64::MAX % m + 1) % m;
+        let r2 = (r as u128 * r as u128 % m as u128) as u64;
+        Self { m, minv, r2 }
+    }
+    pub fn modulus(&self) -> u64 { self.m }
+    /// $t\\cdot 2^{-64} \\bmod m$。ただし $t \\lt m\\cdot 2^{64}$ とする。
+    pub fn reduce(&self, t: u128) -> u64 {
+        let q = (t as u64).wrapping_mul(self.minv);
+        let h = (t >> 64) as u64;
+        let qm = ((q as u128 * self.m as u128) >> 64) as u64;
+        if h >= qm {
+            h - qm
+        } else {
+            h.wrapping_add(self.m - qm)
+        }
+    }
+    /// $a \\bmod m$ を Montgomery 表現に変換する。ただし $a \\lt m$ とする。
+    pub fn encode(&self, a: u64) -> u64 {
+        self.reduce(a as u128 * self.r2 as u128)
+    }
+    pub fn decode(&self, a: u64) -> u64 { self.reduce(a as u128) }
+    pub fn mul(&self, a: u64, b: u64) -> u64 {
+        self.reduce(a as u128 * b as u128)
+    }
+}
+
+pub struct MontgomeryModInt<Id>(u64, PhantomData<fn() -> Id>);
+
+impl<Id: MontgomeryModIntId> MontgomeryModInt<Id> {
+    pub fn new(val: impl RemEuclidU64) -> Self { Self::from(val) }
+    /// 法を設定する。すでに異なる法が設定されていれば panic する。
+    pub fn set_modulus(m: u64) {
+        let mont = Id::montgomery().get_or_init(|| Montgomery::new(m));
+        assert_eq!(mont.modulus(), m, "modulus is already set");
+    }
+    /// 法を返す。法が設定されていなければ panic する。
+    pub fn modulus() -> u64 { Self::montgomery().modulus() }
+    pub fn get(self) -> u64 { Self::montgomery().decode(self.0) }
+    fn montgomery() -> &'static Montgomery {
+        Id::montgomery().get().expect("modulus is not set")
+    }
+}
+
+impl<Id> Clone for MontgomeryModInt<Id> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<Id> Copy for MontgomeryModInt<Id> {}
+
+impl<Id> PartialEq for MontgomeryModInt<Id> {
+    fn eq(&self, other: &Self) -> bool { self.0 == other.0 }
+}
+
+impl<Id> Eq for MontgomeryModInt<Id> {}
+
+impl<Id> Default for MontgomeryModInt<Id> {
+    fn default() -> Self { Self(0, PhantomData) }
+}
+
+impl<Id: MontgomeryModIntId> AddAssign for MontgomeryModInt<Id> {
+    fn add_assign(&mut self, rhs: Self) {
+        let m = Self::modulus();
+        let (tmp, carry) = self.0.overflowing_add(rhs.0);
+        self.0 = if carry || tmp >= m { tmp.wrapping_sub(m) } else { tmp };
+    }
+}
+
+impl<Id: MontgomeryModIntId> SubAssign for MontgomeryModInt<Id> {
+    fn sub_assign(&mut self, rhs: Self) {
+        if self.0 < rhs.0 {
+            self.0 = self.0.wrapping_add(Self::modulus());
+        }
+        self.0 = self.0.wrapping_sub(rhs.0);
+    }
+}
+
+impl<Id: MontgomeryModIntId> MulAssign for MontgomeryModInt<Id> {
+    fn mul_assign(&mut self, rhs: Self) {
+        self.0 = Self::montgomery().mul(self.0, rhs.0);
+    }
+}
+
+impl<Id: MontgomeryModIntId> MontgomeryModInt<Id> {
+    pub fn recip(self) -> Self { self.checked_recip().unwrap() }
+    pub fn checked_recip(self) -> Option<Self> {
+        let m = Self::modulus() as u128;
+        let (g, r) = (self.get() as u128).gcd_recip(m);
+        (g == 1).then_some(Self::new(r))
+    }
+}
+
+impl<Id: MontgomeryModIntId, I: RemEuclidU64> From<I> for MontgomeryModInt<Id> {
+    fn from(val: I) -> Self {
+        let mont = Self::montgomery();
+        let val = val.rem_euclid_u64(mont.modulus());
+        Self(mont.encode(val), PhantomData)
     }
 }
 
+impl<Id> Hash for MontgomeryModInt<Id> {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.0.hash(state) }
+}
+
 macro_rules! impl_bin_op_inner {
-    ( $(
-        impl<_> $op_trait:ident<$(&$ltr:lifetime)? Self> for $(&$ltl:lifetime)? Self {
+    (
+        [$($gen:tt)*] $ty:ty,
+        impl $op_trait:ident<[$($rhs:tt)*]> for [$($lhs:tt)*] {
             fn $op:ident(..) -> _ { self.$op_assign:ident() }
         }
-    )* ) => { $(
-        impl<const MOD: u32> $op_trait<$(&$ltr)? StaticModInt<MOD>> for $(&$ltl)? StaticModInt<MOD> {
-            type Output = StaticModInt<MOD>;
-            fn $op(self, rhs: $(&$ltr)? StaticModInt<MOD>) -> Self::Output {
+    ) => {
+        impl<$($gen)*> $op_trait<$($rhs)*> for $($lhs)* {
+            type Output = $ty;
+            fn $op(self, rhs: $($rhs)*) -> $ty {
                 let mut tmp = self.to_owned();
                 tmp.$op_assign(rhs);
                 tmp
             }
         }
-    )* };
+    };
 }
 
 macro_rules! impl_bin_op {
-    ( $( ($op:ident, $op_trait:ident, $op_assign:ident, $op_assign_trait:ident), )* ) => { $(
+    ( $gen:tt $ty:ty; $( ($op:ident, $op_trait:ident, $op_assign:ident, $op_assign_trait:ident), )* ) => { $(
         impl_bin_op_inner! {
-            impl<_> $op_trait<Self> for Self { fn $op(..) -> _ { self.$op_assign() } }
-            impl<_> $op_trait<&'_ Self> for Self { fn $op(..) -> _ { self.$op_assign() } }
-            impl<_> $op_trait<Self> for &'_ Self { fn $op(..) -> _ { self.$op_assign() } }
-            impl<_> $op_trait<&'_ Self> for &'_ Self { fn $op(..) -> _ { self.$op_assign() } }
+            $gen $ty, impl $op_trait<[$ty]> for [$ty] { fn $op(..) -> _ { self.$op_assign() } }
         }
-        impl<const MOD: u32> $op_assign_trait<&Self> for StaticModInt<MOD> {
-            fn $op_assign(&mut self, rhs: &Self) { self.$op_assign(rhs.to_owned()) }
+        impl_bin_op_inner! {
+            $gen $ty, impl $op_trait<[&'_ $ty]> for [$ty] { fn $op(..) -> _ { self.$op_assign() } }
+        }
+        impl_bin_op_inner! {
+            $gen $ty, impl $op_trait<[$ty]> for [&'_ $ty] { fn $op(..) -> _ { self.$op_assign() } }
         }
+        impl_bin_op_inner! {
+            $gen $ty, impl $op_trait<[&'_ $ty]> for [&'_ $ty] { fn $op(..) -> _ { self.$op_assign() } }
+        }
+        impl_assign_ref! { $gen $ty, $op_assign_trait, $op_assign }
     )* }
 }
 
-impl_bin_op! {
-    ( add, Add, add_assign, AddAssign ),
-    ( sub, Sub, sub_assign, SubAssign ),
-    ( mul, Mul, mul_assign, MulAssign ),
-    ( div, Div, div_assign, DivAssign ),
+macro_rules! impl_assign_ref {
+    ( [$($gen:tt)*] $ty:ty, $op_assign_trait:ident, $op_assign:ident ) => {
+        impl<$($gen)*> $op_assign_trait<&$ty> for $ty {
+            fn $op_assign(&mut self, rhs: &$ty) { self.$op_assign(rhs.to_owned()) }
+        }
+    };
 }
 
-impl<const MOD: u32> Neg for StaticModInt<MOD> {
-    type Output = StaticModInt<MOD>;
-    fn neg(self) -> Self::Output {
-        if self.0 == 0 { self } else { StaticModInt(MOD - self.0) }
-    }
+macro_rules! impl_folding_inner {
+    (
+        [$($gen:tt)*] $ty:ty,
+        impl<$($lt:lifetime)?> $op_trait:ident<[$($item:tt)*]> {
+            fn $op:ident(..) -> _ { $unit:literal; self.$op_assign:ident() }
+        }
+    ) => {
+        impl<$($lt,)? $($gen)*> $op_trait<$($item)*> for $ty {
+            fn $op<I: Iterator<Item = $($item)*>>(iter: I) -> $ty {
+                let mut res = <$ty>::new($unit);
+                for x in iter {
+                    res.$op_assign(x);
+                }
+                res
+            }
+        }
+    };
 }
 
-impl<const MOD: u32> Neg for &StaticModInt<MOD> {
-    type Output = StaticModInt<MOD>;
-    fn neg(self) -> Self::Output {
-        if self.0 == 0 { *self } else { StaticModInt(MOD - self.0) }
-    }
+macro_rules! impl_folding {
+    ( $gen:tt $ty:ty; $( ($op:ident, $op_trait:ident, $op_assign:ident, $unit:literal), )* ) => { $(
+        impl_folding_inner! {
+            $gen $ty, impl<> $op_trait<[$ty]> { fn $op(..) -> _ { $unit; self.$op_assign() } }
+        }
+        impl_folding_inner! {
+            $gen $ty, impl<'a> $op_trait<[&'a $ty]> { fn $op(..) -> _ { $unit; self.$op_assign() } }
+        }
+    )* }
 }
 
-impl<const MOD: u32> fmt::Display for StaticModInt<MOD> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.0.fmt(f) }
+macro_rules! impl_modint {
+    ( $( impl[$($gen:tt)*] ModInt for $ty:ty; )* ) => { $(
+        impl<$($gen)*> DivAssign for $ty {
+            fn div_assign(&mut self, rhs: Self) { self.mul_assign(rhs.recip()) }
+        }
+
+        impl_bin_op! {
+            [$($gen)*] $ty;
+            ( add, Add, add_assign, AddAssign ),
+            ( sub, Sub, sub_assign, SubAssign ),
+            ( mul, Mul, mul_assign, MulAssign ),
+            ( div, Div, div_assign, DivAssign ),
+        }
+
+        impl<$($gen)*> Neg for $ty {
+            type Output = $ty;
+            fn neg(self) -> $ty { <$ty>::default() - self }
+        }
+
+        impl<$($gen)*> Neg for &$ty {
+            type Output = $ty;
+            fn neg(self) -> $ty { <$ty>::default() - self }
+        }
+
+        impl<$($gen)*> fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.get().fmt(f)
+            }
+        }
+
+        impl<$($gen)*> fmt::Debug for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} mod {}", self, <$ty>::modulus())
+            }
+        }
+
+        impl_folding! {
+            [$($gen)*] $ty;
+            ( sum, Sum, add_assign, 0 ),
+            ( product, Product, mul_assign, 1 ),
+        }
+
+        impl<$($gen)*> $ty {
+            pub fn pow(self, exp: impl BinIter) -> Self {
+                let mut res = Self::new(1);
+                let mut dbl = self;
+                for b in exp.bin_iter() {
+                    if b {
+                        res *= dbl;
+                    }
+                    dbl *= dbl;
+                }
+                res
+            }
+        }
+
+        impl<$($gen)*> ModInt for $ty {
+            fn new(val: impl RemEuclidU32) -> Self { Self::new(val) }
+            fn get(self) -> u32 {
+                let val = self.get_u64();
+                val.try_into().unwrap_or_else(|_| {
+                    panic!("value does not fit in u32: {val}; use get_u64")
+                })
+            }
+            fn pow(self, exp: impl BinIter) -> Self { self.pow(exp) }
+            fn recip(self) -> Self { self.recip() }
+            fn checked_recip(self) -> Option<Self> { self.checked_recip() }
+            fn modulus_u64() -> u64 { Self::modulus().into() }
+            fn get_u64(self) -> u64 { self.get().into() }
+        }
+    )* }
 }
 
-impl<const MOD: u32> fmt::Debug for StaticModInt<MOD> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} mod {}", self, MOD)
-    }
+impl_modint! {
+    impl[const MOD: u32] ModInt for StaticModInt<MOD>;
+    impl[Id: DynamicModIntId] ModInt for DynamicModInt<Id>;
+    impl[Id: MontgomeryModIntId] ModInt for MontgomeryModInt<Id>;
 }
 
-pub trait RemEuclidU32 {
+pub trait RemEuclidU32: RemEuclidU64 {
     fn rem_euclid_u32(&self, rem: u32) -> u32;
 }
 
-macro_rules! impl_rem_euclid_u32 {
-    ( $( ($lhs:ty, $common:ty) ),* ) => { $(
-        impl RemEuclidU32 for $lhs {
-            fn rem_euclid_u32(&self, rem: u32) -> u32 {
-                (*self as $common).rem_euclid(rem as $common) as u32
+pub trait RemEuclidU64 {
+    fn rem_euclid_u64(&self, rem: u64) -> u64;
+}
+
+macro_rules! impl_rem_euclid {
+    ( $trait:ident::$method:ident -> $uint:ty; $( ($lhs:ty, $common:ty) ),* ) => { $(
+        impl $trait for $lhs {
+            fn $method(&self, rem: $uint) -> $uint {
+                (*self as $common).rem_euclid(rem as $common) as $uint
             }
         }
     )* }
 }
 
+macro_rules! impl_rem_euclid_u32 {
+    ( $( ($lhs:ty, $common:ty) ),* ) => {
+        impl_rem_euclid! { RemEuclidU32::rem_euclid_u32 -> u32; $( ($lhs, $common) ),* }
+    }
+}
+
 macro_rules! impl_rem_euclid_u32_small {
     ( $($lhs:ty)* ) => { impl_rem_euclid_u32! { $( ($lhs, u32) ),* } }
 }
@@ -159,47 +527,10 @@ impl_rem_euclid_u32_small! { u8 u16 u32 }
 impl_rem_euclid_u32_large! { i64 i128 u64 u128 }
 impl_rem_euclid_u32! { (i8, i32), (i16, i32), (i32, i64), (isize, isize), (usize, usize) }
 
-impl<const MOD: u32, I: RemEuclidU32> From<I> for StaticModInt<MOD> {
-    fn from(val: I) -> Self { Self(val.rem_euclid_u32(MOD)) }
-}
-
-macro_rules! impl_folding_inner {
-    ( $(
-        impl<$($lt:lifetime,)? _> $op_trait:ident<$(&$ltr:lifetime)? Self> for Self {
-            fn $op:ident(..) -> _ { $unit:literal; self.$op_assign:ident($($deref:tt)?) }
-        }
-    )* ) => { $(
-        impl<$($lt,)? const MOD: u32> $op_trait<$(&$ltr)? StaticModInt<MOD>> for StaticModInt<MOD> {
-            fn $op<I>(iter: I) -> StaticModInt<MOD>
-            where
-                I: Iterator<Item = $(&$ltr)? StaticModInt<MOD>>,
-            {
-                let mut res = StaticModInt::new($unit);
-                for x in iter {
-                    res.$op_assign(x);
-                }
-                res
-            }
-        }
-    )* };
-}
-
-macro_rules! impl_folding {
-    ( $( ($op:ident, $op_trait:ident, $op_assign:ident, $unit:literal), )* ) => { $(
-        impl_folding_inner! {
-            impl<_> $op_trait<Self> for Self { fn $op(..) -> _ { $unit; self.$op_assign() } }
-            impl<'a, _> $op_trait<&'a Self> for Self { fn $op(..) -> _ { $unit; self.$op_assign() } }
-        }
-    )* }
-}
-
-impl_folding! {
-    ( sum, Sum, add_assign, 0 ),
-    ( product, Product, mul_assign, 1 ),
-}
-
-impl<const MOD: u32> Hash for StaticModInt<MOD> {
-    fn hash<H: Hasher>(&self, state: &mut H) { self.0.hash(state) }
+impl_rem_euclid! {
+    RemEuclidU64::rem_euclid_u64 -> u64;
+    (u8, u64), (u16, u64), (u32, u64), (u64, u64), (u128, u128), (usize, u64),
+    (i8, i128), (i16, i128), (i32, i128), (i64, i128), (i128, i128), (isize, i128)
 }
 
 pub trait ModInt:
@@ -216,7 +547,7 @@ pub trait ModInt:
     + MulAssign<Self>
     + Div<Self, Output = Self>
     + DivAssign<Self>
-    + Neg
+    + Neg<Output = Self>
     + Sum
     + Product
 {
@@ -225,14 +556,11 @@ pub trait ModInt:
     fn pow(self, exp: impl BinIter) -> Self;
     fn recip(self) -> Self;
     fn checked_recip(self) -> Option<Self>;
-}
 
-impl<const MOD: u32> ModInt for StaticModInt<MOD> {
-    fn new(val: impl RemEuclidU32) -> Self { Self::new(val) }
-    fn get(self) -> u32 { self.0 }
-    fn pow(self, exp: impl BinIter) -> Self { self.pow(exp) }
-    fn recip(self) -> Self { self.recip() }
-    fn checked_recip(self) -> Option<Self> { self.checked_recip() }
+    /// 法を返す。$2^{32}$ 以上の法も扱える。
+    fn modulus_u64() -> u64 { u64::from((-Self::new(1)).get()) + 1 }
+    /// 値を返す。[`get`](ModInt::get) と異なり、$2^{32}$ 以上の値も扱える。
+    fn get_u64(self) -> u64 { self.get().into() }
 }
 
 pub type ModInt998244353 = StaticModInt<998244353>;
@@ -315,3 +643,97 @@ fn conversion() {
     assert_eq!(Mi::new(10_u128).0, 10);
     assert_eq!(Mi::new(10_usize).0, 10);
 }
+
+#[test]
+fn dynamic() {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    enum Id {}
+    impl DynamicModIntId for Id {
+        fn barrett() -> &'static OnceLock<Barrett> {
+            static BARRETT: OnceLock<Barrett> = OnceLock::new();
+            &BARRETT
+        }
+    }
+    type Mi = DynamicModInt<Id>;
+
+    let m = 2_u32.pow(31) + 11; // not a prime
+    Mi::set_modulus(m);
+    Mi::set_modulus(m);
+    assert_eq!(Mi::modulus(), m);
+
+    let mut rng = ChaCha20Rng::from_seed([0; 32]);
+    let mut rand = || rng.gen::<u64>();
+    let m64 = m as u64;
+    for _ in 0..1000 {
+        let (a, b) = (rand(), rand());
+        let (x, y) = (Mi::new(a), Mi::new(b));
+        assert_eq!((x + y).get() as u64, (a % m64 + b % m64) % m64);
+        assert_eq!((x - y).get() as u64, (a % m64 + m64 - b % m64) % m64);
+        assert_eq!((x * y).get() as u64, (a % m64) * (b % m64) % m64);
+        if let Some(r) = y.checked_recip() {
+            assert_eq!(r * y, Mi::new(1));
+            assert_eq!(x / y * y, x);
+        }
+    }
+    assert_eq!(Mi::new(-1).get(), m - 1);
+    assert_eq!((Mi::new(m - 1) + Mi::new(m - 1)).get(), m - 2);
+    assert_eq!((Mi::new(0) - Mi::new(m - 1)).get(), 1);
+    assert_eq!((Mi::new(m - 2) - Mi::new(m - 1)).get(), m - 1);
+    assert_eq!(format!("{:?}", Mi::new(3)), format!("3 mod {m}"));
+
+    enum Id1 {}
+    impl DynamicModIntId for Id1 {
+        fn barrett() -> &'static OnceLock<Barrett> {
+            static BARRETT: OnceLock<Barrett> = OnceLock::new();
+            &BARRETT
+        }
+    }
+    DynamicModInt::<Id1>::set_modulus(1);
+    let zero = DynamicModInt::<Id1>::new(5) * DynamicModInt::<Id1>::new(7);
+    assert_eq!(zero.get(), 0);
+}
+
+#[test]
+fn montgomery() {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    enum Id {}
+    impl MontgomeryModIntId for Id {
+        fn montgomery() -> &'static OnceLock<Montgomery> {
+            static MONTGOMERY: OnceLock<Montgomery> = OnceLock::new();
+            &MONTGOMERY
+        }
+    }
+    type Mi = MontgomeryModInt<Id>;
+
+    let m = u64::MAX - 58; // the largest prime below 2^64
+    Mi::set_modulus(m);
+    assert_eq!(Mi::modulus(), m);
+
+    let mut rng = ChaCha20Rng::from_seed([0; 32]);
+    let mut rand = || rng.gen::<u64>();
+    let m128 = m as u128;
+    for _ in 0..1000 {
+        let (a, b) = (rand(), rand());
+        let (x, y) = (Mi::new(a), Mi::new(b));
+        let (a, b) = (a as u128 % m128, b as u128 % m128);
+        assert_eq!(x.get() as u128, a);
+        assert_eq!((x + y).get() as u128, (a + b) % m128);
+        assert_eq!((x - y).get() as u128, (a + m128 - b) % m128);
+        assert_eq!((x * y).get() as u128, a * b % m128);
+        if let Some(r) = y.checked_recip() {
+            assert_eq!(r * y, Mi::new(1));
+        }
+    }
+    assert_eq!(Mi::new(-1_i64).get(), m - 1);
+    assert_eq!(<Mi as ModInt>::modulus_u64(), m);
+    assert_eq!(ModInt::get_u64(Mi::new(-1_i64)), m - 1);
+    assert_eq!(ModInt::get(Mi::new(5)), 5_u32);
+    assert_eq!(Mi::new(2).pow(m - 1), Mi::new(1));
+    assert_eq!(Mi::new(3).pow(m), Mi::new(3));
+    assert_eq!(Mi::new(3).pow(0_u32), Mi::new(1));
+    assert_eq!([Mi::new(2); 70].iter().product::<Mi>(), Mi::new(2).pow(70_u32));
+}
@@ -0,0 +1,131 @@
+use factorial_table::FactorialTable;
+use fps::Fps;
+use modint::ModInt;
+
+/// 多点評価。
+///
+/// $f(x_0), f(x_1), \\dots, f(x_{n-1})$ を $O(n\\log(n)^2 + |f|\\log|f|)$
+/// 時間で返す。
+pub fn multipoint_eval<M: ModInt>(f: &Fps<M>, xs: &[M]) -> Vec<M> {
+    let n = xs.len();
+    if n == 0 {
+        return vec![];
+    }
+    let tree = SubproductTree::new(xs);
+    let size = tree.size;
+    let mut rem = vec![Fps::new(); 2 * size];
+    rem[1] = f.div_rem(&tree.buf[1]).1;
+    for i in 2..size + n {
+        rem[i] = rem[i / 2].div_rem(&tree.buf[i]).1;
+    }
+    (0..n).map(|i| rem[size + i].get(0)).collect()
+}
+
+/// 点 $(x_i, y_i)$ を通る次数 $n$ 未満の多項式。
+///
+/// $x_i$ は相異なるとする。
+pub fn interpolate<M: ModInt>(xs: &[M], ys: &[M]) -> Fps<M> {
+    assert_eq!(xs.len(), ys.len());
+    let n = xs.len();
+    if n == 0 {
+        return Fps::new();
+    }
+    let tree = SubproductTree::new(xs);
+    let size = tree.size;
+    // w_i = y_i / prod_{j != i} (x_i - x_j)
+    let denom = multipoint_eval(&tree.buf[1].derivative(), xs);
+    let mut res = vec![Fps::new(); 2 * size];
+    for i in 0..n {
+        res[size + i] = Fps::from(vec![ys[i] / denom[i]]);
+    }
+    for i in (1..size).rev() {
+        res[i] = &res[2 * i] * &tree.buf[2 * i + 1]
+            + &res[2 * i + 1] * &tree.buf[2 * i];
+    }
+    let mut res = std::mem::take(&mut res[1]);
+    res.trim();
+    res
+}
+
+/// $f(0), f(1), \\dots, f(n-1)$ から、次数 $n$ 未満の多項式 $f$ の $f(x)$
+/// を $O(n)$ 時間で求める。
+pub fn interpolate_iota<M: ModInt>(ys: &[M], x: M) -> M {
+    let n = ys.len();
+    if n == 0 {
+        return M::new(0);
+    }
+    let ft = FactorialTable::<M>::new(n - 1);
+    // prefix[i] = prod_{j < i} (x - j)
+    let mut prefix = vec![M::new(1); n + 1];
+    for i in 0..n {
+        prefix[i + 1] = prefix[i] * (x - M::new(i));
+    }
+    let mut suffix = M::new(1);
+    let mut res = M::new(0);
+    for i in (0..n).rev() {
+        let num = prefix[i] * suffix;
+        let denom = ft.factorial_recip(i) * ft.factorial_recip(n - 1 - i);
+        let term = ys[i] * num * denom;
+        if (n - 1 - i).is_multiple_of(2) {
+            res += term;
+        } else {
+            res -= term;
+        }
+        suffix *= x - M::new(i);
+    }
+    res
+}
+
+struct SubproductTree<M> {
+    size: usize,
+    buf: Vec<Fps<M>>,
+}
+
+impl<M: ModInt> SubproductTree<M> {
+    fn new(xs: &[M]) -> Self {
+        let n = xs.len();
+        let size = n.next_power_of_two();
+        let mut buf = vec![Fps::from(vec![M::new(1)]); 2 * size];
+        for i in 0..n {
+            buf[size + i] = Fps::from(vec![-xs[i], M::new(1)]);
+        }
+        for i in (1..size).rev() {
+            buf[i] = &buf[2 * i] * &buf[2 * i + 1];
+        }
+        Self { size, buf }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use modint::ModInt998244353;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    type Mi = ModInt998244353;
+
+    #[test]
+    fn random() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut rand = || Mi::new(rng.gen::<u32>());
+        for (deg, n) in [(0, 0), (0, 3), (5, 1), (10, 10), (100, 70), (60, 200)]
+        {
+            let f = Fps::from((0..deg).map(|_| rand()).collect::<Vec<_>>());
+            let xs: Vec<_> = (0..n).map(|_| rand()).collect();
+            let expected: Vec<_> = xs.iter().map(|&x| f.eval(x)).collect();
+            assert_eq!(multipoint_eval(&f, &xs), expected);
+
+            let ys: Vec<_> = (0..n).map(|_| rand()).collect();
+            let g = interpolate(&xs, &ys);
+            assert!(g.len() <= n);
+            assert!(xs.iter().zip(&ys).all(|(&x, &y)| g.eval(x) == y));
+
+            let ys: Vec<_> = (0..deg).map(|i| f.eval(Mi::new(i))).collect();
+            for x in [Mi::new(0), Mi::new(3), Mi::new(deg), rand()] {
+                assert_eq!(interpolate_iota(&ys, x), f.eval(x));
+            }
+        }
+    }
+}
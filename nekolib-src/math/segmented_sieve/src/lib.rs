@@ -0,0 +1,102 @@
+use std::ops::Range;
+
+use linear_sieve::LinearSieve;
+
+const BLOCK_LEN: u64 = 1 << 16;
+
+/// 区間 $[l, r)$ の素数を昇順に列挙する。
+///
+/// $\\sqrt{r}$ 以下の素数で長さ $2^{16}$ のブロックごとに篩う。
+/// 全体で $O(\\sqrt{r} + (r-l)\\log\\log(r))$ 時間、$O(\\sqrt{r}+2^{16})$ 空間。
+///
+/// # Examples
+/// ```
+/// use segmented_sieve::SegmentedSieve;
+///
+/// let primes: Vec<_> = SegmentedSieve::new(90..110).collect();
+/// assert_eq!(primes, [97, 101, 103, 107, 109]);
+/// ```
+pub struct SegmentedSieve {
+    base: Vec<u64>,
+    next: u64,
+    end: u64,
+    block: Vec<u64>,
+    pos: usize,
+}
+
+impl SegmentedSieve {
+    pub fn new(range: Range<u64>) -> Self {
+        let Range { start, end } = range;
+        let start = start.max(2);
+        let sqrt = end.saturating_sub(1).isqrt();
+        let base = LinearSieve::new(sqrt as usize)
+            .primes()
+            .map(|p| p as u64)
+            .collect();
+        Self { base, next: start, end, block: vec![], pos: 0 }
+    }
+
+    fn sieve_block(&mut self) {
+        let lo = self.next;
+        let hi = self.end.min(lo.saturating_add(BLOCK_LEN));
+        let mut is_prime = vec![true; (hi - lo) as usize];
+        for &p in self.base.iter().take_while(|&&p| p * p < hi) {
+            let first = (p * p).max(lo.div_ceil(p) * p);
+            for j in (first..hi).step_by(p as usize) {
+                is_prime[(j - lo) as usize] = false;
+            }
+        }
+        self.block.clear();
+        self.block
+            .extend((lo..hi).zip(is_prime).filter(|&(_, b)| b).map(|(i, _)| i));
+        self.pos = 0;
+        self.next = hi;
+    }
+}
+
+impl Iterator for SegmentedSieve {
+    type Item = u64;
+    fn next(&mut self) -> Option<u64> {
+        while self.pos == self.block.len() {
+            if self.next >= self.end {
+                return None;
+            }
+            self.sieve_block();
+        }
+        self.pos += 1;
+        Some(self.block[self.pos - 1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn naive() {
+        let n = 3000;
+        let ls = LinearSieve::new(n);
+        for l in 0..=200 {
+            for r in (l..=n).step_by(37).chain([n]) {
+                let actual: Vec<_> =
+                    SegmentedSieve::new(l as u64..r as u64).collect();
+                let expected: Vec<_> = (l..r)
+                    .filter(|&i| ls.is_prime(i))
+                    .map(|i| i as u64)
+                    .collect();
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn large() {
+        let lo = 10_u64.pow(12);
+        let primes: Vec<_> = SegmentedSieve::new(lo..lo + 200).collect();
+        let is_prime = |n: u64| (2..=n.isqrt()).all(|d| !n.is_multiple_of(d));
+        let expected: Vec<_> =
+            (lo..lo + 200).filter(|&n| is_prime(n)).collect();
+        assert_eq!(primes, expected);
+        assert_eq!(SegmentedSieve::new(0..10_u64.pow(7)).count(), 664579);
+    }
+}
@@ -1,14 +1,24 @@
 use inner::doc_inline_reexport;
 
 doc_inline_reexport! {
+    binom_mod,
     convolution,
+    discrete_log,
+    divisor_zeta,
     divisors,
     factorial_table,
     factors,
+    floor_sum,
+    fps,
     garner,
     gcd,
     gcd_recip,
+    linear_recurrence,
     linear_sieve,
+    min25_sieve,
     modint,
+    multipoint,
+    segmented_sieve,
     stern_brocot,
+    subset_convolution,
 }
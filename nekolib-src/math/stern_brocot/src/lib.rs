@@ -1,4 +1,5 @@
 use std::{
+    cmp::Ordering,
     fmt,
     iter::FusedIterator,
     ops::{Add, ControlFlow, Div, Mul, Sub},
@@ -44,6 +45,34 @@ impl<I: fmt::Display> fmt::Debug for Fraction<I> {
     }
 }
 
+// Denominators are assumed to be positive.
+impl<I: Ord> PartialEq for Fraction<I>
+where
+    for<'a> &'a I: Mul<&'a I, Output = I>,
+{
+    fn eq(&self, other: &Self) -> bool { self.cmp(other) == Ordering::Equal }
+}
+
+impl<I: Ord> Eq for Fraction<I> where for<'a> &'a I: Mul<&'a I, Output = I> {}
+
+impl<I: Ord> PartialOrd for Fraction<I>
+where
+    for<'a> &'a I: Mul<&'a I, Output = I>,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<I: Ord> Ord for Fraction<I>
+where
+    for<'a> &'a I: Mul<&'a I, Output = I>,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.numer * &other.denom).cmp(&(&other.numer * &self.denom))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum ApproxBound<T> {
     Lower(T),
@@ -0,0 +1,215 @@
+use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+
+use has_zero::HasZero;
+
+/// $\\hat{a}\_S = \\sum_{T\\subseteq S} a_T$。
+pub fn subset_zeta<T: Copy + AddAssign>(a: &mut [T]) {
+    let n = a.len();
+    assert!(n.is_power_of_two());
+    for bit in (0..).map(|i| 1 << i).take_while(|&bit| bit < n) {
+        for s in (0..n).filter(|s| s & bit != 0) {
+            let tmp = a[s ^ bit];
+            a[s] += tmp;
+        }
+    }
+}
+
+/// [`subset_zeta`] の逆変換。
+pub fn subset_mobius<T: Copy + SubAssign>(a: &mut [T]) {
+    let n = a.len();
+    assert!(n.is_power_of_two());
+    for bit in (0..).map(|i| 1 << i).take_while(|&bit| bit < n) {
+        for s in (0..n).filter(|s| s & bit != 0) {
+            let tmp = a[s ^ bit];
+            a[s] -= tmp;
+        }
+    }
+}
+
+/// $\\hat{a}\_S = \\sum_{T\\supseteq S} a_T$。
+pub fn superset_zeta<T: Copy + AddAssign>(a: &mut [T]) {
+    let n = a.len();
+    assert!(n.is_power_of_two());
+    for bit in (0..).map(|i| 1 << i).take_while(|&bit| bit < n) {
+        for s in (0..n).filter(|s| s & bit == 0) {
+            let tmp = a[s | bit];
+            a[s] += tmp;
+        }
+    }
+}
+
+/// [`superset_zeta`] の逆変換。
+pub fn superset_mobius<T: Copy + SubAssign>(a: &mut [T]) {
+    let n = a.len();
+    assert!(n.is_power_of_two());
+    for bit in (0..).map(|i| 1 << i).take_while(|&bit| bit < n) {
+        for s in (0..n).filter(|s| s & bit == 0) {
+            let tmp = a[s | bit];
+            a[s] -= tmp;
+        }
+    }
+}
+
+/// Walsh–Hadamard 変換。正規化は行わないので、二回適用すると $n$ 倍になる。
+pub fn walsh_hadamard<T>(a: &mut [T])
+where
+    T: Copy + Add<Output = T> + Sub<Output = T>,
+{
+    let n = a.len();
+    assert!(n.is_power_of_two());
+    for bit in (0..).map(|i| 1 << i).take_while(|&bit| bit < n) {
+        for s in (0..n).filter(|s| s & bit == 0) {
+            let (x, y) = (a[s], a[s | bit]);
+            a[s] = x + y;
+            a[s | bit] = x - y;
+        }
+    }
+}
+
+/// $c_S = \\sum_{T\\cup U = S} a_T b_U$。
+pub fn or_convolve<T>(a: &[T], b: &[T]) -> Vec<T>
+where
+    T: Copy + AddAssign + SubAssign + Mul<Output = T>,
+{
+    assert_eq!(a.len(), b.len());
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    subset_zeta(&mut a);
+    subset_zeta(&mut b);
+    let mut c: Vec<_> = a.into_iter().zip(b).map(|(x, y)| x * y).collect();
+    subset_mobius(&mut c);
+    c
+}
+
+/// $c_S = \\sum_{T\\cap U = S} a_T b_U$。
+pub fn and_convolve<T>(a: &[T], b: &[T]) -> Vec<T>
+where
+    T: Copy + AddAssign + SubAssign + Mul<Output = T>,
+{
+    assert_eq!(a.len(), b.len());
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    superset_zeta(&mut a);
+    superset_zeta(&mut b);
+    let mut c: Vec<_> = a.into_iter().zip(b).map(|(x, y)| x * y).collect();
+    superset_mobius(&mut c);
+    c
+}
+
+/// $c_S = \\sum_{T\\oplus U = S} a_T b_U$。
+///
+/// 最後に長さで割るので、`T` では $2$ による除算ができる必要がある。
+pub fn xor_convolve<T>(a: &[T], b: &[T]) -> Vec<T>
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + From<u32>,
+{
+    assert_eq!(a.len(), b.len());
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    walsh_hadamard(&mut a);
+    walsh_hadamard(&mut b);
+    let mut c: Vec<_> = a.into_iter().zip(b).map(|(x, y)| x * y).collect();
+    walsh_hadamard(&mut c);
+    let n = T::from(c.len() as u32);
+    c.into_iter().map(|x| x / n).collect()
+}
+
+/// $c_S = \\sum_{T\\cup U = S, T\\cap U = \\emptyset} a_T b_U$。
+///
+/// 長さ $2^n$ に対して $O(2^n n^2)$ 時間。
+pub fn subset_convolve<T>(a: &[T], b: &[T]) -> Vec<T>
+where
+    T: Copy + AddAssign + SubAssign + Mul<Output = T> + HasZero,
+{
+    let len = a.len();
+    assert_eq!(len, b.len());
+    assert!(len.is_power_of_two());
+    let n = len.trailing_zeros() as usize;
+    let ranked = |a: &[T]| {
+        let mut res = vec![vec![T::zero(); len]; n + 1];
+        for (s, &x) in a.iter().enumerate() {
+            res[s.count_ones() as usize][s] = x;
+        }
+        for r in &mut res {
+            subset_zeta(r);
+        }
+        res
+    };
+    let ha = ranked(a);
+    let hb = ranked(b);
+    let mut hc = vec![vec![T::zero(); len]; n + 1];
+    for i in 0..=n {
+        for j in 0..=n - i {
+            for s in 0..len {
+                hc[i + j][s] += ha[i][s] * hb[j][s];
+            }
+        }
+    }
+    for r in &mut hc {
+        subset_mobius(r);
+    }
+    (0..len).map(|s| hc[s.count_ones() as usize][s]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use modint::ModInt998244353;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    type Mi = ModInt998244353;
+
+    fn naive(
+        a: &[i64],
+        b: &[i64],
+        f: impl Fn(usize, usize) -> Option<usize>,
+    ) -> Vec<i64> {
+        let mut res = vec![0; a.len()];
+        for (i, &ai) in a.iter().enumerate() {
+            for (j, &bj) in b.iter().enumerate() {
+                if let Some(k) = f(i, j) {
+                    res[k] += ai * bj;
+                }
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut rand = || rng.gen_range(-100..=100_i64);
+        for n in 0..=8 {
+            let len = 1 << n;
+            let a: Vec<_> = (0..len).map(|_| rand()).collect();
+            let b: Vec<_> = (0..len).map(|_| rand()).collect();
+
+            assert_eq!(or_convolve(&a, &b), naive(&a, &b, |i, j| Some(i | j)));
+            assert_eq!(and_convolve(&a, &b), naive(&a, &b, |i, j| Some(i & j)));
+            assert_eq!(xor_convolve(&a, &b), naive(&a, &b, |i, j| Some(i ^ j)));
+            let expected = naive(&a, &b, |i, j| (i & j == 0).then_some(i | j));
+            assert_eq!(subset_convolve(&a, &b), expected);
+
+            let am: Vec<_> = a.iter().map(|&x| Mi::new(x)).collect();
+            let bm: Vec<_> = b.iter().map(|&x| Mi::new(x)).collect();
+            let expected: Vec<_> = naive(&a, &b, |i, j| Some(i ^ j))
+                .into_iter()
+                .map(Mi::new)
+                .collect();
+            assert_eq!(xor_convolve(&am, &bm), expected);
+            let expected: Vec<_> =
+                naive(&a, &b, |i, j| (i & j == 0).then_some(i | j))
+                    .into_iter()
+                    .map(Mi::new)
+                    .collect();
+            assert_eq!(subset_convolve(&am, &bm), expected);
+        }
+    }
+}
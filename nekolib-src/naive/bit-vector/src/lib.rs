@@ -10,6 +10,8 @@
 
 use std::ops::Range;
 
+use rank_select::RankSelect;
+
 pub struct RankIndexNlC(Vec<usize>);
 
 impl RankIndexNlC {
@@ -97,6 +99,18 @@ impl Rs01DictNlC {
     pub fn select0(&self, i: usize) -> usize { self.select::<false>(i) }
 }
 
+impl RankSelect for Rs01DictNlC {
+    fn len(&self) -> usize { self.rank_index.0.len() }
+    fn rank1(&self, i: usize) -> usize { self.rank1(i) }
+    fn rank0(&self, i: usize) -> usize { self.rank0(i) }
+    fn select1(&self, i: usize) -> usize { self.select1(i) }
+    fn select0(&self, i: usize) -> usize { self.select0(i) }
+}
+
+impl From<&[bool]> for Rs01DictNlC {
+    fn from(a: &[bool]) -> Self { Self::new(a) }
+}
+
 struct RankIndexNLl {
     block: Vec<usize>,
     buf: Vec<u64>,
@@ -214,6 +228,7 @@ const SPARSE_LEN: usize = 4096; // log(n)^2
 pub type Rs01DictNLl = Rs01DictNLlParam<POPCNT, SPARSE_LEN>;
 
 pub struct Rs01DictNLlParam<const POPCNT: usize, const SPARSE_LEN: usize> {
+    len: usize,
     rank_index: RankIndexNLl,
     select1_index: SelectIndexNLl<POPCNT, SPARSE_LEN>,
     select0_index: SelectIndexNLl<POPCNT, SPARSE_LEN>,
@@ -224,6 +239,7 @@ impl<const POPCNT: usize, const SPARSE_LEN: usize>
 {
     pub fn new(a: &[bool]) -> Self {
         Self {
+            len: a.len(),
             rank_index: RankIndexNLl::new(a),
             select1_index: SelectIndexNLl::new::<true>(a),
             select0_index: SelectIndexNLl::new::<false>(a),
@@ -246,6 +262,22 @@ impl<const POPCNT: usize, const SPARSE_LEN: usize>
     pub fn select0(&self, i: usize) -> usize { self.select::<false>(i) }
 }
 
+impl<const POPCNT: usize, const SPARSE_LEN: usize> RankSelect
+    for Rs01DictNLlParam<POPCNT, SPARSE_LEN>
+{
+    fn len(&self) -> usize { self.len }
+    fn rank1(&self, i: usize) -> usize { self.rank1(i) }
+    fn rank0(&self, i: usize) -> usize { self.rank0(i) }
+    fn select1(&self, i: usize) -> usize { self.select1(i) }
+    fn select0(&self, i: usize) -> usize { self.select0(i) }
+}
+
+impl<const POPCNT: usize, const SPARSE_LEN: usize> From<&[bool]>
+    for Rs01DictNLlParam<POPCNT, SPARSE_LEN>
+{
+    fn from(a: &[bool]) -> Self { Self::new(a) }
+}
+
 #[test]
 fn sanity_check() {
     // % echo 0b_$(shuf -e {0,1}{0,1}{0,1}{0,1} | paste -sd _ -)_u64
@@ -0,0 +1,357 @@
+use std::ops::Range;
+
+const NONE: usize = 1_usize.wrapping_neg();
+
+/// Aho–Corasick 法のオートマトン。
+///
+/// 状態はトライの頂点で、根 `0` から BFS 順に番号づけられている。
+/// 特に、`fail(s) < s` (`s > 0`) や `parent(s) < s` が成り立つ。
+/// 遷移 `goto` はパターンに現れる文字について前計算されている。
+pub struct AhoCorasick<T: Ord> {
+    alphabet: Vec<T>,
+    goto: Vec<usize>,
+    parent: Vec<usize>,
+    depth: Vec<usize>,
+    fail: Vec<usize>,
+    output_link: Vec<usize>,
+    // patterns ending at each state, as ranges on `ends`.
+    ends_start: Vec<usize>,
+    ends: Vec<usize>,
+    pattern_state: Vec<usize>,
+}
+
+impl<T: Ord + Clone> From<Vec<Vec<T>>> for AhoCorasick<T> {
+    fn from(pats: Vec<Vec<T>>) -> Self {
+        let mut alphabet: Vec<_> = pats.iter().flatten().cloned().collect();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+        Self::new(&pats, alphabet)
+    }
+}
+
+impl AhoCorasick<u8> {
+    pub fn from_bytes(pats: Vec<Vec<u8>>) -> Self {
+        let mut seen = [false; 256];
+        for &b in pats.iter().flatten() {
+            seen[b as usize] = true;
+        }
+        let alphabet = (0..=255).filter(|&b| seen[b as usize]).collect();
+        Self::new(&pats, alphabet)
+    }
+}
+
+impl AhoCorasick<char> {
+    pub fn from_chars(pats: Vec<Vec<char>>) -> Self { Self::from(pats) }
+}
+
+impl AhoCorasick<usize> {
+    /// 文字が `0..k` からなり、それぞれ現れるとする。
+    pub fn from_hashed(pats: Vec<Vec<usize>>) -> Self {
+        let k = pats.iter().flatten().map(|&x| x + 1).max().unwrap_or(0);
+        let mut seen = vec![false; k];
+        for &x in pats.iter().flatten() {
+            seen[x] = true;
+        }
+        assert!(seen.iter().all(|&b| b), "not hashed");
+        Self::new(&pats, (0..k).collect())
+    }
+}
+
+impl<T: Ord> AhoCorasick<T> {
+    fn new(pats: &[Vec<T>], alphabet: Vec<T>) -> Self {
+        let sigma = alphabet.len();
+        let code = |x: &T| alphabet.binary_search(x).unwrap();
+
+        // the trie, in the insertion order
+        let mut next = vec![vec![NONE; sigma]];
+        let mut pattern_node = vec![];
+        for pat in pats {
+            let mut v = 0;
+            for x in pat {
+                let c = code(x);
+                if next[v][c] == NONE {
+                    next[v][c] = next.len();
+                    next.push(vec![NONE; sigma]);
+                }
+                v = next[v][c];
+            }
+            pattern_node.push(v);
+        }
+
+        // renumber in the BFS order
+        let n = next.len();
+        let mut order = vec![0];
+        let mut id = vec![NONE; n];
+        id[0] = 0;
+        let mut goto = vec![NONE; n * sigma];
+        let mut parent = vec![NONE; n];
+        let mut depth = vec![0; n];
+        for head in 0..n {
+            let v = order[head];
+            for c in 0..sigma {
+                let u = next[v][c];
+                if u != NONE {
+                    id[u] = order.len();
+                    order.push(u);
+                    goto[head * sigma + c] = id[u];
+                    parent[id[u]] = head;
+                    depth[id[u]] = depth[head] + 1;
+                }
+            }
+        }
+        let pattern_state: Vec<_> =
+            pattern_node.iter().map(|&v| id[v]).collect();
+
+        let mut ends_start = vec![0; n + 1];
+        for &s in &pattern_state {
+            ends_start[s + 1] += 1;
+        }
+        for s in 0..n {
+            ends_start[s + 1] += ends_start[s];
+        }
+        let mut ends = vec![0; pats.len()];
+        let mut cur = ends_start.clone();
+        for (i, &s) in pattern_state.iter().enumerate() {
+            ends[cur[s]] = i;
+            cur[s] += 1;
+        }
+
+        let mut fail = vec![0; n];
+        let mut output_link = vec![NONE; n];
+        for s in 0..n {
+            for c in 0..sigma {
+                let t = goto[s * sigma + c];
+                if t != NONE {
+                    let f = if s == 0 { 0 } else { goto[fail[s] * sigma + c] };
+                    fail[t] = f;
+                    output_link[t] = if ends_start[f] < ends_start[f + 1] {
+                        f
+                    } else {
+                        output_link[f]
+                    };
+                } else {
+                    goto[s * sigma + c] =
+                        if s == 0 { 0 } else { goto[fail[s] * sigma + c] };
+                }
+            }
+        }
+
+        Self {
+            alphabet,
+            goto,
+            parent,
+            depth,
+            fail,
+            output_link,
+            ends_start,
+            ends,
+            pattern_state,
+        }
+    }
+
+    /// 状態数。
+    pub fn num_states(&self) -> usize { self.depth.len() }
+    pub fn num_patterns(&self) -> usize { self.pattern_state.len() }
+    /// パターンに現れる文字を昇順に並べたもの。
+    pub fn alphabet(&self) -> &[T] { &self.alphabet }
+
+    /// トライ上の子。
+    pub fn child(&self, s: usize, x: &T) -> Option<usize> {
+        let t = self.goto(s, x);
+        (t != 0 && self.parent[t] == s).then_some(t)
+    }
+    /// トライ上の親。
+    pub fn parent(&self, s: usize) -> Option<usize> {
+        Some(self.parent[s]).filter(|&p| p != NONE)
+    }
+    /// 根からの深さ、すなわち状態の表す文字列の長さ。
+    pub fn depth(&self, s: usize) -> usize { self.depth[s] }
+
+    /// 状態 `s` から `x` を読んだ後の状態。
+    pub fn goto(&self, s: usize, x: &T) -> usize {
+        match self.alphabet.binary_search(x) {
+            Ok(c) => self.goto[s * self.alphabet.len() + c],
+            Err(_) => 0,
+        }
+    }
+    /// `s` の表す文字列の真の接尾辞であって、トライに含まれる最長のもの。
+    pub fn fail(&self, s: usize) -> usize { self.fail[s] }
+    /// `s` の表す文字列の真の接尾辞であって、パターンであるもののうち
+    /// 最長のもの。
+    pub fn output_link(&self, s: usize) -> Option<usize> {
+        Some(self.output_link[s]).filter(|&t| t != NONE)
+    }
+    /// `s` の表す文字列と等しいパターンの番号。
+    pub fn patterns(&self, s: usize) -> &[usize] {
+        &self.ends[self.ends_start[s]..self.ends_start[s + 1]]
+    }
+    /// `i` 番目のパターンを表す状態。
+    pub fn pattern_state(&self, i: usize) -> usize { self.pattern_state[i] }
+
+    /// 出現を `(パターンの番号, 出現範囲)` として、出現範囲の終端の昇順、
+    /// 同じ終端では長い順に列挙する。
+    pub fn find_iter<'a, I: IntoIterator<Item = &'a T>>(
+        &'a self,
+        text: I,
+    ) -> FindIter<'a, T, I::IntoIter> {
+        FindIter {
+            ac: self,
+            text: text.into_iter(),
+            state: 0,
+            pos: 0,
+            out: NONE,
+            k: 0,
+        }
+    }
+
+    /// 各パターンの出現回数。
+    pub fn count<'a>(&self, text: impl IntoIterator<Item = &'a T>) -> Vec<usize>
+    where
+        T: 'a,
+    {
+        let mut visits = vec![0; self.num_states()];
+        let mut s = 0;
+        for x in text {
+            s = self.goto(s, x);
+            visits[s] += 1;
+        }
+        for s in (1..self.num_states()).rev() {
+            visits[self.fail[s]] += visits[s];
+        }
+        self.pattern_state.iter().map(|&s| visits[s]).collect()
+    }
+}
+
+pub struct FindIter<'a, T: Ord, I> {
+    ac: &'a AhoCorasick<T>,
+    text: I,
+    state: usize,
+    pos: usize,
+    out: usize,
+    k: usize,
+}
+
+impl<'a, T: Ord + 'a, I: Iterator<Item = &'a T>> Iterator
+    for FindIter<'a, T, I>
+{
+    type Item = (usize, Range<usize>);
+    fn next(&mut self) -> Option<(usize, Range<usize>)> {
+        loop {
+            if self.out != NONE {
+                if let Some(&i) = self.ac.patterns(self.out).get(self.k) {
+                    self.k += 1;
+                    let start = self.pos - self.ac.depth[self.out];
+                    return Some((i, start..self.pos));
+                }
+                self.out = self.ac.output_link[self.out];
+                self.k = 0;
+                continue;
+            }
+            let x = self.text.next()?;
+            self.state = self.ac.goto(self.state, x);
+            self.pos += 1;
+            self.out = self.state;
+            self.k = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    #[test]
+    fn sanity_check() {
+        let pats = ["he", "she", "his", "hers"];
+        let ac = AhoCorasick::from_bytes(
+            pats.map(|p| p.as_bytes().to_vec()).to_vec(),
+        );
+        assert_eq!(ac.num_states(), 10);
+        assert_eq!(ac.alphabet(), b"ehirs");
+
+        let she = ac.pattern_state(1);
+        let he = ac.pattern_state(0);
+        assert_eq!(ac.depth(she), 3);
+        assert_eq!(ac.fail(she), he);
+        assert_eq!(ac.output_link(she), Some(he));
+        assert_eq!(ac.output_link(he), None);
+        assert_eq!(ac.patterns(he), [0]);
+        let sh = ac.parent(she).unwrap();
+        assert_eq!(ac.child(sh, &b'e'), Some(she));
+        assert_eq!(ac.child(sh, &b'i'), None);
+        assert_eq!(
+            ac.goto(sh, &b'i'),
+            ac.child(ac.child(0, &b'h').unwrap(), &b'i').unwrap()
+        );
+        assert_eq!(ac.goto(she, &b'x'), 0);
+        assert_eq!(ac.parent(0), None);
+
+        let text = b"ushers";
+        let found: Vec<_> = ac.find_iter(text).collect();
+        assert_eq!(found, [(1, 1..4), (0, 2..4), (3, 2..6)]);
+        assert_eq!(ac.count(text), [1, 1, 0, 1]);
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut rand = |n: usize| rng.gen_range(0..n);
+        for sigma in [1, 2, 3, 5] {
+            let pats: Vec<Vec<usize>> = (0..30)
+                .map(|_| (0..1 + rand(5)).map(|_| rand(sigma)).collect())
+                .collect();
+            let text: Vec<_> = (0..300).map(|_| rand(sigma + 1)).collect();
+            let mut expected = vec![];
+            for r in 1..=text.len() {
+                let mut here: Vec<_> = pats
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, p)| text[..r].ends_with(p))
+                    .map(|(i, p)| (i, r - p.len()..r))
+                    .collect();
+                here.sort_by_key(|(i, range)| (range.start, *i));
+                expected.extend(here);
+            }
+            let count: Vec<_> = (0..pats.len())
+                .map(|i| expected.iter().filter(|&&(j, _)| i == j).count())
+                .collect();
+
+            let ac = AhoCorasick::from(pats.clone());
+            let mut actual: Vec<_> = ac.find_iter(&text).collect();
+            // the order among the same range is unspecified
+            for w in actual.windows(2) {
+                let (l, r) = (&w[0].1, &w[1].1);
+                assert!((l.end, l.start) <= (r.end, r.start));
+            }
+            actual.sort_by_key(|(i, range)| (range.end, range.start, *i));
+            assert_eq!(actual, expected);
+            assert_eq!(ac.count(&text), count);
+
+            if (0..sigma).all(|x| pats.iter().flatten().any(|&y| x == y)) {
+                let ac_hashed = AhoCorasick::from_hashed(pats.clone());
+                assert_eq!(ac_hashed.count(&text), count);
+            }
+
+            for s in 1..ac.num_states() {
+                assert!(ac.fail(s) < s);
+                assert!(ac.parent(s).unwrap() < s);
+                assert_eq!(ac.depth(s), ac.depth(ac.parent(s).unwrap()) + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn chars() {
+        let pats: Vec<Vec<char>> = ["ab", "b", "", "bab"]
+            .iter()
+            .map(|p| p.chars().collect())
+            .collect();
+        let ac = AhoCorasick::from_chars(pats);
+        let text: Vec<_> = "abab".chars().collect();
+        assert_eq!(ac.count(&text), [2, 2, 4, 1]);
+        assert_eq!(ac.find_iter(&text).filter(|(i, _)| *i == 2).count(), 4);
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+
+const NONE: usize = 1_usize.wrapping_neg();
+
+/// 回文木 (eertree)。
+///
+/// 文字列 $S$ の相異なる回文部分文字列を頂点とする木を、末尾への文字の
+/// 追加に対して償却 $O(\\log\\sigma)$ 時間で更新する。頂点 `0` は長さ
+/// $-1$ の、頂点 `1` は長さ $0$ の仮想的な回文を表し、それ以降の頂点は
+/// 追加された順に番号づけられる。特に、`link(v) < v` (`v > 1`) が成り立つ。
+pub struct Eertree<T> {
+    buf: Vec<T>,
+    next: Vec<BTreeMap<T, usize>>,
+    link: Vec<usize>,
+    // the length of each palindrome, with `NONE` for -1.
+    len: Vec<usize>,
+    // the number of palindromic suffixes of each palindrome.
+    depth: Vec<usize>,
+    // the number of positions where each node is the longest palindromic
+    // suffix.
+    last_count: Vec<usize>,
+    last: usize,
+    total: usize,
+}
+
+impl<T: Ord + Clone> Eertree<T> {
+    pub fn new() -> Self {
+        Self {
+            buf: vec![],
+            next: vec![BTreeMap::new(), BTreeMap::new()],
+            link: vec![0, 0],
+            len: vec![NONE, 0],
+            depth: vec![0, 0],
+            last_count: vec![0, 0],
+            last: 1,
+            total: 0,
+        }
+    }
+
+    /// `x` を末尾に追加し、新たな回文が現れたかを返す。
+    pub fn push(&mut self, x: T) -> bool {
+        self.buf.push(x.clone());
+        let mut v = self.suffix_with(self.last);
+        let new = !self.next[v].contains_key(&x);
+        if new {
+            let len = self.len[v].wrapping_add(2);
+            let link = if len == 1 {
+                1
+            } else {
+                let u = self.suffix_with(self.link[v]);
+                self.next[u][&x]
+            };
+            self.next.push(BTreeMap::new());
+            self.link.push(link);
+            self.len.push(len);
+            self.depth.push(self.depth[link] + 1);
+            self.last_count.push(0);
+            let w = self.len.len() - 1;
+            self.next[v].insert(x.clone(), w);
+        }
+        v = self.next[v][&x];
+        self.last = v;
+        self.last_count[v] += 1;
+        self.total += self.depth[v];
+        new
+    }
+
+    // the longest palindromic suffix `v` of the node, such that `xvx` is
+    // a suffix, where `x` is the last character.
+    fn suffix_with(&self, mut v: usize) -> usize {
+        let i = self.buf.len() - 1;
+        loop {
+            let j = i.wrapping_sub(self.len[v]).wrapping_sub(1);
+            if j <= i && self.buf[j] == self.buf[i] {
+                return v;
+            }
+            v = self.link[v];
+        }
+    }
+}
+
+impl<T: Ord> Eertree<T> {
+    /// これまでに追加した文字列の長さ。
+    pub fn len(&self) -> usize { self.buf.len() }
+    pub fn is_empty(&self) -> bool { self.buf.is_empty() }
+    pub fn num_nodes(&self) -> usize { self.len.len() }
+
+    /// 頂点 `v` の表す回文の長さ。頂点 `0` に対しては `None`。
+    pub fn node_len(&self, v: usize) -> Option<usize> {
+        Some(self.len[v]).filter(|&l| l != NONE)
+    }
+    /// 頂点 `v` の両端に `x` を付け加えた回文。
+    pub fn transition(&self, v: usize, x: &T) -> Option<usize> {
+        self.next[v].get(x).copied()
+    }
+    /// 頂点 `v` の最長の真の回文接尾辞。
+    pub fn link(&self, v: usize) -> usize { self.link[v] }
+    /// 文字列全体の最長の回文接尾辞。
+    pub fn last(&self) -> usize { self.last }
+
+    /// 空でない相異なる回文部分文字列の個数。
+    pub fn count_distinct(&self) -> usize { self.num_nodes() - 2 }
+    /// 空でない回文部分文字列の個数 (出現位置が異なれば区別する)。
+    pub fn count_all(&self) -> usize { self.total }
+
+    /// 各頂点の表す回文の出現回数。頂点 `0`, `1` に対しては `0` とする。
+    pub fn occurrences(&self) -> Vec<usize> {
+        let mut res = self.last_count.clone();
+        for v in (2..self.num_nodes()).rev() {
+            res[self.link[v]] += res[v];
+        }
+        res[0] = 0;
+        res[1] = 0;
+        res
+    }
+}
+
+impl<T: Ord + Clone> Default for Eertree<T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T: Ord + Clone> From<Vec<T>> for Eertree<T> {
+    fn from(buf: Vec<T>) -> Self {
+        let mut res = Self::new();
+        for x in buf {
+            res.push(x);
+        }
+        res
+    }
+}
+
+impl From<String> for Eertree<char> {
+    fn from(buf: String) -> Self { buf.chars().collect::<Vec<_>>().into() }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+    use std::collections::BTreeMap;
+
+    use crate::*;
+
+    #[test]
+    fn sanity_check() {
+        let et = Eertree::from("eertree".to_owned());
+        // e, r, t, ee, rtr, ertre, eertree
+        assert_eq!(et.count_distinct(), 7);
+        assert_eq!(et.count_all(), 12);
+        assert_eq!(et.node_len(et.last()), Some(7));
+        assert_eq!(et.node_len(0), None);
+        assert_eq!(et.node_len(1), Some(0));
+        let occ = et.occurrences();
+        let e = et.transition(0, &'e').unwrap();
+        assert_eq!(occ[e], 4);
+        let ee = et.transition(1, &'e').unwrap();
+        assert_eq!(occ[ee], 2);
+        assert_eq!(et.link(ee), e);
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut rand = |n: usize| rng.gen_range(0..n);
+        for sigma in [1, 2, 3, 26] {
+            let mut et = Eertree::new();
+            let mut text = vec![];
+            let mut palindromes = BTreeMap::new();
+            for _ in 0..80 {
+                let x = rand(sigma);
+                text.push(x);
+                let n = text.len();
+                let mut new = false;
+                for l in 0..n {
+                    let s = &text[l..];
+                    if s.iter().eq(s.iter().rev()) {
+                        let count = palindromes.entry(s.to_vec()).or_insert(0);
+                        new |= *count == 0;
+                        *count += 1;
+                    }
+                }
+                assert_eq!(et.push(x), new);
+                assert_eq!(et.count_distinct(), palindromes.len());
+                assert_eq!(et.count_all(), palindromes.values().sum::<usize>());
+            }
+
+            let occ = et.occurrences();
+            for v in 2..et.num_nodes() {
+                assert!(et.link(v) < v);
+            }
+            for (pal, &count) in &palindromes {
+                // walk from the center
+                let m = pal.len();
+                let mut v = if m % 2 == 0 { 1 } else { 0 };
+                for x in pal[m / 2..].iter() {
+                    v = et.transition(v, x).unwrap();
+                }
+                assert_eq!(et.node_len(v), Some(m));
+                assert_eq!(occ[v], count);
+            }
+        }
+    }
+}
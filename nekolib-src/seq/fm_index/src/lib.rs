@@ -0,0 +1,207 @@
+use std::ops::{Range, RangeBounds};
+
+use rs01_dict::Rs01Dict;
+use suffix_array::SuffixArray;
+use usize_bounds::UsizeBounds;
+use wavelet_matrix::WaveletMatrix;
+
+/// FM-index。
+///
+/// 文字列 $S$ ($|S| = n$, 文字種 $\\sigma$) の BWT を wavelet matrix
+/// で持つ。パターン $P$ ($|P| = m$) の出現回数を $O(m\\log\\sigma)$
+/// 時間で数える。接尾辞配列は `rate` おきに標本化して持ち、出現位置の
+/// 列挙は一つあたり $O(\\text{rate}\\cdot\\log\\sigma)$ 時間、長さ $\\ell$
+/// の部分文字列の復元は $O((\\ell+\\text{rate})\\log\\sigma)$ 時間でできる。
+pub struct FmIndex<T> {
+    len: usize,
+    rate: usize,
+    // symbols sorted; the code of `alphabet[i]` is `i + 1`, and `$` is `0`.
+    alphabet: Vec<T>,
+    bwt: WaveletMatrix<usize>,
+    // the number of symbols less than each code, with the sentinel.
+    less: Vec<usize>,
+    // whether `sa[i]` is sampled, i.e. a multiple of `rate`.
+    sampled: Rs01Dict,
+    sa_sample: Vec<usize>,
+    // `isa[j * rate]`, and `isa[n]`.
+    isa_sample: Vec<usize>,
+}
+
+impl<T: Ord + Clone> FmIndex<T> {
+    pub fn new(sa: &SuffixArray<T>, rate: usize) -> Self {
+        assert!(rate > 0, "sampling rate must be positive");
+
+        let text = sa.text();
+        let len = text.len();
+        let mut alphabet = text.to_vec();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+        let code = |x: &T| alphabet.binary_search(x).unwrap() + 1;
+
+        let mut less = vec![0; alphabet.len() + 2];
+        less[1] = 1;
+        for x in text {
+            less[code(x) + 1] += 1;
+        }
+        for i in 1..less.len() {
+            less[i] += less[i - 1];
+        }
+
+        let mut bwt = Vec::with_capacity(len + 1);
+        let mut sampled = vec![false; len + 1];
+        let mut sa_sample = vec![];
+        let mut isa_sample = vec![0; len / rate + 2];
+        for i in 0..=len {
+            let j = sa[i];
+            bwt.push(if j == 0 { 0 } else { code(&text[j - 1]) });
+            if j.is_multiple_of(rate) {
+                sampled[i] = true;
+                sa_sample.push(j);
+                isa_sample[j / rate] = i;
+            }
+            if j == len {
+                *isa_sample.last_mut().unwrap() = i;
+            }
+        }
+
+        let bwt = WaveletMatrix::from(bwt);
+        let sampled = Rs01Dict::new(&sampled);
+        Self { len, rate, alphabet, bwt, less, sampled, sa_sample, isa_sample }
+    }
+
+    pub fn len(&self) -> usize { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// `pat` の出現回数を返す。
+    pub fn count(&self, pat: &[T]) -> usize {
+        let Range { start, end } = self.range(pat);
+        end - start
+    }
+
+    /// `pat` の出現位置を、接尾辞配列の順に返す。
+    pub fn locate(&self, pat: &[T]) -> Vec<usize> {
+        self.range(pat).map(|i| self.sa(i)).collect()
+    }
+
+    /// `range` の部分文字列を返す。
+    pub fn extract(&self, range: impl RangeBounds<usize>) -> Vec<T> {
+        let Range { start, end } = range.to_range(self.len);
+        if start == end {
+            return vec![];
+        }
+        let j = end.div_ceil(self.rate);
+        let (mut pos, mut i) = if j * self.rate >= self.len {
+            (self.len, *self.isa_sample.last().unwrap())
+        } else {
+            (j * self.rate, self.isa_sample[j])
+        };
+        let mut res = vec![];
+        while pos > start {
+            let c = self.bwt.get(i);
+            pos -= 1;
+            if pos < end {
+                res.push(self.alphabet[c - 1].clone());
+            }
+            i = self.lf(i, c);
+        }
+        res.reverse();
+        res
+    }
+
+    fn range(&self, pat: &[T]) -> Range<usize> {
+        let (mut start, mut end) = (0, self.len + 1);
+        for x in pat.iter().rev() {
+            let c = match self.alphabet.binary_search(x) {
+                Ok(c) => c + 1,
+                Err(_) => return 0..0,
+            };
+            start = self.lf(start, c);
+            end = self.lf(end, c);
+            if start == end {
+                return 0..0;
+            }
+        }
+        start..end
+    }
+
+    // the number of suffixes preceded by `c` and less than `sa[i]`,
+    // plus the number of suffixes starting with symbols less than `c`.
+    fn lf(&self, i: usize, c: usize) -> usize {
+        self.less[c] + self.bwt.count(..i, c)
+    }
+
+    fn sa(&self, mut i: usize) -> usize {
+        let mut steps = 0;
+        while !self.sampled.get(i) {
+            i = self.lf(i, self.bwt.get(i));
+            steps += 1;
+        }
+        self.sa_sample[self.sampled.rank1(i) - 1] + steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    #[test]
+    fn sanity_check() {
+        let text = b"abracadabra".to_vec();
+        let sa = SuffixArray::from_bytes(text.clone());
+        for rate in [1, 2, 3, 5, 16] {
+            let fm = FmIndex::new(&sa, rate);
+            assert_eq!(fm.len(), text.len());
+            assert_eq!(fm.count(b"abra"), 2);
+            assert_eq!(fm.count(b"a"), 5);
+            assert_eq!(fm.count(b"abrac"), 1);
+            assert_eq!(fm.count(b"x"), 0);
+            assert_eq!(fm.count(b"rab"), 0);
+            assert_eq!(fm.locate(b"abra"), [7, 0]);
+            assert_eq!(fm.locate(b""), sa.search(b"").collect::<Vec<_>>());
+            for l in 0..=text.len() {
+                for r in l..=text.len() {
+                    assert_eq!(fm.extract(l..r), &text[l..r]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn empty_text() {
+        let sa = SuffixArray::<char>::from(String::new());
+        let fm = FmIndex::new(&sa, 4);
+        assert!(fm.is_empty());
+        assert_eq!(fm.count(&[]), 1);
+        assert_eq!(fm.count(&['a']), 0);
+        assert_eq!(fm.locate(&[]), [0]);
+        assert!(fm.extract(..).is_empty());
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut rand = |n: u64| rng.gen_range(0..n);
+        for sigma in [1, 2, 4, 26] {
+            let text: Vec<_> = (0..300).map(|_| rand(sigma) as u32).collect();
+            let sa = SuffixArray::from(text.clone());
+            let fm = FmIndex::new(&sa, 1 + rand(10) as usize);
+            for _ in 0..200 {
+                let len = 1 + rand(4) as usize;
+                let pat: Vec<_> =
+                    (0..len).map(|_| rand(sigma + 1) as u32).collect();
+                let expected: Vec<_> = sa.search(&pat).collect();
+                assert_eq!(fm.count(&pat), expected.len());
+                assert_eq!(fm.locate(&pat), expected);
+            }
+            for _ in 0..200 {
+                let l = rand(301) as usize;
+                let r = l + rand(301 - l as u64) as usize;
+                assert_eq!(fm.extract(l..r), &text[l..r]);
+            }
+            assert_eq!(fm.extract(..), text);
+        }
+    }
+}
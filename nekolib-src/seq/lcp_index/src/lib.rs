@@ -0,0 +1,193 @@
+use std::{
+    cmp::Ordering,
+    ops::{Range, RangeBounds},
+};
+
+use n1_rmq::N1Rmq;
+use suffix_array::SuffixArray;
+use usize_bounds::UsizeBounds;
+
+/// 接尾辞配列と LCP 配列による文字列のクエリ。
+///
+/// 接尾辞配列、その逆配列、LCP 配列の RMQ を持ち、二つの接尾辞の
+/// 最長共通接頭辞 (LCE) を $O(1)$ 時間で求める。
+pub struct LcpIndex<T: Ord> {
+    sa: SuffixArray<T>,
+    rank: Vec<usize>,
+    lcp: N1Rmq<usize>,
+    // the number of distinct substrings that are prefixes of
+    // `sa[1]`, ..., `sa[i]`.
+    distinct: Vec<usize>,
+}
+
+impl<T: Ord> From<SuffixArray<T>> for LcpIndex<T> {
+    fn from(sa: SuffixArray<T>) -> Self {
+        let n = sa.text().len();
+        let mut rank = vec![0; n + 1];
+        for i in 0..=n {
+            rank[sa[i]] = i;
+        }
+        let lcpa = sa.lcpa();
+        let mut distinct = vec![0; n + 1];
+        for i in 1..=n {
+            distinct[i] = distinct[i - 1] + (n - sa[i] - lcpa[i]);
+        }
+        let lcp = N1Rmq::from(lcpa);
+        Self { sa, rank, lcp, distinct }
+    }
+}
+
+impl<T: Ord> LcpIndex<T> {
+    pub fn len(&self) -> usize { self.rank.len() - 1 }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    pub fn suffix_array(&self) -> &SuffixArray<T> { &self.sa }
+
+    /// `i` 番目からの接尾辞と `j` 番目からの接尾辞の最長共通接頭辞の長さ。
+    pub fn lce(&self, i: usize, j: usize) -> usize {
+        if i == j {
+            return self.len() - i;
+        }
+        let (ri, rj) = (self.rank[i], self.rank[j]);
+        let (lo, hi) = if ri < rj { (ri, rj) } else { (rj, ri) };
+        *self.lcp.min(lo + 1, hi + 1)
+    }
+
+    /// 部分文字列 `a` と `b` を辞書順で比較する。
+    pub fn compare_substrings(
+        &self,
+        a: impl RangeBounds<usize>,
+        b: impl RangeBounds<usize>,
+    ) -> Ordering {
+        let a = a.to_range(self.len());
+        let b = b.to_range(self.len());
+        let (a_len, b_len) = (a.end - a.start, b.end - b.start);
+        if a_len.min(b_len) == 0 {
+            return a_len.cmp(&b_len);
+        }
+        if self.lce(a.start, b.start) >= a_len.min(b_len) {
+            a_len.cmp(&b_len)
+        } else {
+            self.rank[a.start].cmp(&self.rank[b.start])
+        }
+    }
+
+    /// 空でない相異なる部分文字列の個数。
+    pub fn count_distinct(&self) -> usize { self.distinct[self.len()] }
+
+    /// 空でない相異なる部分文字列のうち、辞書順で `k` 番目 (0-indexed)
+    /// のものの出現位置の一つを返す。
+    pub fn kth_substring(&self, k: usize) -> Option<Range<usize>> {
+        if k >= self.count_distinct() {
+            return None;
+        }
+        let i = self.distinct.partition_point(|&x| x <= k);
+        let start = self.sa[i];
+        let lcp = *self.lcp.min(i, i + 1);
+        let len = lcp + (k - self.distinct[i - 1]) + 1;
+        Some(start..start + len)
+    }
+
+    /// 二回以上現れる部分文字列のうち最長のものの出現位置の一つを返す。
+    pub fn longest_repeated_substring(&self) -> Range<usize> {
+        let n = self.len();
+        let (len, i) = (2..=n)
+            .map(|i| (*self.lcp.min(i, i + 1), i))
+            .max()
+            .unwrap_or((0, 0));
+        let start = self.sa[i];
+        start..start + len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+    use std::collections::BTreeSet;
+
+    use crate::*;
+
+    #[test]
+    fn sanity_check() {
+        let text = b"abracadabra";
+        let index = LcpIndex::from(SuffixArray::from_bytes(text.to_vec()));
+        assert_eq!(index.lce(0, 7), 4);
+        assert_eq!(index.lce(3, 5), 1);
+        assert_eq!(index.lce(1, 2), 0);
+        assert_eq!(index.lce(4, 4), 7);
+        assert_eq!(index.lce(11, 0), 0);
+        assert_eq!(index.compare_substrings(0..4, 7..11), Ordering::Equal);
+        assert_eq!(index.compare_substrings(0..4, 7..10), Ordering::Greater);
+        assert_eq!(index.compare_substrings(1..3, 3..4), Ordering::Greater);
+        assert_eq!(&text[index.longest_repeated_substring()], b"abra");
+        assert_eq!(index.kth_substring(0), Some(10..11));
+        assert_eq!(index.kth_substring(index.count_distinct()), None);
+    }
+
+    #[test]
+    fn empty_text() {
+        let index = LcpIndex::from(SuffixArray::from_bytes(vec![]));
+        assert!(index.is_empty());
+        assert_eq!(index.lce(0, 0), 0);
+        assert_eq!(index.count_distinct(), 0);
+        assert_eq!(index.kth_substring(0), None);
+        assert_eq!(index.longest_repeated_substring(), 0..0);
+        assert_eq!(index.compare_substrings(.., ..), Ordering::Equal);
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut rand = |n: u64| rng.gen_range(0..n);
+        for (len, sigma) in [(1, 1), (30, 1), (40, 2), (60, 3), (80, 26)] {
+            let text: Vec<_> =
+                (0..len).map(|_| b'a' + rand(sigma) as u8).collect();
+            let index = LcpIndex::from(SuffixArray::from_bytes(text.clone()));
+            let n = text.len();
+
+            for i in 0..=n {
+                for j in 0..=n {
+                    let lce = text[i..]
+                        .iter()
+                        .zip(&text[j..])
+                        .take_while(|(x, y)| x == y)
+                        .count();
+                    assert_eq!(index.lce(i, j), lce);
+                }
+            }
+
+            let substrings: BTreeSet<_> = (0..n)
+                .flat_map(|l| (l + 1..=n).map(move |r| l..r))
+                .map(|r| &text[r])
+                .collect();
+            assert_eq!(index.count_distinct(), substrings.len());
+            for (k, &s) in substrings.iter().enumerate() {
+                assert_eq!(index.kth_substring(k).map(|r| &text[r]), Some(s));
+            }
+
+            let occ = |s: &[u8]| {
+                (0..=n - s.len()).filter(|&i| text[i..].starts_with(s)).count()
+            };
+            let lrs = substrings
+                .iter()
+                .filter(|s| occ(s) > 1)
+                .map(|s| s.len())
+                .max()
+                .unwrap_or(0);
+            let actual = index.longest_repeated_substring();
+            assert_eq!(actual.len(), lrs);
+            assert!(occ(&text[actual]) > 1);
+
+            for _ in 0..500 {
+                let l1 = rand(n as u64 + 1) as usize;
+                let r1 = l1 + rand((n - l1) as u64 + 1) as usize;
+                let l2 = rand(n as u64 + 1) as usize;
+                let r2 = l2 + rand((n - l2) as u64 + 1) as usize;
+                assert_eq!(
+                    index.compare_substrings(l1..r1, l2..r2),
+                    text[l1..r1].cmp(&text[l2..r2])
+                );
+            }
+        }
+    }
+}
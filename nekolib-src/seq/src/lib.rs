@@ -1,9 +1,15 @@
 use inner::doc_inline_reexport;
 
 doc_inline_reexport! {
+    aho_corasick,
+    eertree,
+    fm_index,
     inversion,
+    lcp_index,
     lis,
     majority_vote,
     opmph,
+    strings,
     suffix_array,
+    suffix_automaton,
 }
@@ -0,0 +1,427 @@
+use std::{
+    cmp::Ordering::{Equal, Greater, Less},
+    hash::{BuildHasher, DefaultHasher, Hash, Hasher, RandomState},
+    ops::{Range, RangeBounds},
+};
+
+use bisect::Bisect;
+use usize_bounds::UsizeBounds;
+
+pub trait ZArray {
+    /// `i` 番目からの接尾辞と全体の最長共通接頭辞の長さを並べたもの。
+    fn z_array(&self) -> Vec<usize>;
+}
+
+impl<T: Eq> ZArray for [T] {
+    fn z_array(&self) -> Vec<usize> {
+        let n = self.len();
+        let mut z = vec![0; n];
+        if n == 0 {
+            return z;
+        }
+        z[0] = n;
+        let (mut l, mut r) = (0, 0);
+        for i in 1..n {
+            if i < r {
+                z[i] = (r - i).min(z[i - l]);
+            }
+            while i + z[i] < n && self[z[i]] == self[i + z[i]] {
+                z[i] += 1;
+            }
+            if i + z[i] > r {
+                (l, r) = (i, i + z[i]);
+            }
+        }
+        z
+    }
+}
+
+pub trait PrefixFunction {
+    /// `..=i` の最長の真の border の長さを並べたもの。
+    fn prefix_function(&self) -> Vec<usize>;
+}
+
+impl<T: Eq> PrefixFunction for [T] {
+    fn prefix_function(&self) -> Vec<usize> {
+        let n = self.len();
+        let mut pi = vec![0; n];
+        for i in 1..n {
+            let mut j = pi[i - 1];
+            while j > 0 && self[i] != self[j] {
+                j = pi[j - 1];
+            }
+            if self[i] == self[j] {
+                j += 1;
+            }
+            pi[i] = j;
+        }
+        pi
+    }
+}
+
+/// KMP 法によるパターン照合のオートマトン。
+///
+/// 状態はパターンの接頭辞と一致している長さで、`0..=m` の値をとる。
+pub struct Kmp<T> {
+    pat: Vec<T>,
+    fail: Vec<usize>,
+}
+
+impl<T: Eq + Clone> Kmp<T> {
+    pub fn new(pat: &[T]) -> Self {
+        Self { pat: pat.to_vec(), fail: pat.prefix_function() }
+    }
+
+    /// 状態 `state` から `x` を読んだ後の状態。
+    pub fn next(&self, mut state: usize, x: &T) -> usize {
+        let m = self.pat.len();
+        if state == m {
+            if m == 0 {
+                return 0;
+            }
+            state = self.fail[m - 1];
+        }
+        while state > 0 && self.pat[state] != *x {
+            state = self.fail[state - 1];
+        }
+        if self.pat[state] == *x {
+            state += 1;
+        }
+        state
+    }
+
+    /// `text` 中のパターンの出現位置 (先頭) を昇順に返す。
+    pub fn find_all(&self, text: &[T]) -> Vec<usize> {
+        let m = self.pat.len();
+        if m == 0 {
+            return (0..=text.len()).collect();
+        }
+        let mut res = vec![];
+        let mut state = 0;
+        for (i, x) in text.iter().enumerate() {
+            state = self.next(state, x);
+            if state == m {
+                res.push(i + 1 - m);
+            }
+        }
+        res
+    }
+}
+
+pub trait Manacher {
+    /// `l + r == i` なる回文 `self[l..r]` の長さの最大値を並べたもの。
+    /// 長さは `2 * n + 1` となる。
+    fn manacher(&self) -> Vec<usize>;
+}
+
+impl<T: Eq> Manacher for [T] {
+    fn manacher(&self) -> Vec<usize> {
+        // separators interleaved: `$ a $ b $ ... $`
+        let t: Vec<_> = (0..2 * self.len() + 1)
+            .map(|i| (i % 2 == 1).then(|| &self[i / 2]))
+            .collect();
+        let n = t.len();
+        let mut rad = vec![0; n];
+        let (mut c, mut r) = (0, 0);
+        for i in 0..n {
+            let mut k = if i < r { (r - i).min(rad[2 * c - i]) } else { 1 };
+            while k <= i && i + k < n && t[i - k] == t[i + k] {
+                k += 1;
+            }
+            rad[i] = k;
+            if i + k > r {
+                (c, r) = (i, i + k);
+            }
+        }
+        rad.into_iter().map(|k| k - 1).collect()
+    }
+}
+
+pub trait Lyndon {
+    /// Lyndon 分解。各 factor の範囲を返す。
+    fn lyndon_factorization(&self) -> Vec<Range<usize>>;
+    /// 辞書順最小の回転 `self[k..] + self[..k]` を与える最小の `k`。
+    fn min_rotation(&self) -> usize;
+}
+
+impl<T: Ord> Lyndon for [T] {
+    fn lyndon_factorization(&self) -> Vec<Range<usize>> {
+        let n = self.len();
+        let mut res = vec![];
+        let mut i = 0;
+        while i < n {
+            let (mut j, mut k) = (i + 1, i);
+            while j < n && self[k] <= self[j] {
+                k = if self[k] < self[j] { i } else { k + 1 };
+                j += 1;
+            }
+            while i <= k {
+                res.push(i..i + j - k);
+                i += j - k;
+            }
+        }
+        res
+    }
+
+    fn min_rotation(&self) -> usize {
+        let n = self.len();
+        let (mut i, mut j, mut k) = (0, 1, 0);
+        while i < n && j < n && k < n {
+            match self[(i + k) % n].cmp(&self[(j + k) % n]) {
+                Equal => k += 1,
+                Greater => {
+                    i = (i + k + 1).max(j + 1);
+                    k = 0;
+                }
+                Less => {
+                    j = (j + k + 1).max(i + 1);
+                    k = 0;
+                }
+            }
+        }
+        i.min(j)
+    }
+}
+
+const MOD: u64 = (1 << 61) - 1;
+
+fn mul(a: u64, b: u64) -> u64 {
+    let x = a as u128 * b as u128;
+    let res = (x >> 61) as u64 + (x as u64 & MOD);
+    if res >= MOD {
+        res - MOD
+    } else {
+        res
+    }
+}
+
+fn add(a: u64, b: u64) -> u64 {
+    let res = a + b;
+    if res >= MOD {
+        res - MOD
+    } else {
+        res
+    }
+}
+
+/// $\\bmod 2^{61}-1$ のローリングハッシュ。
+///
+/// 同じ基数のもの同士でのみハッシュ値を比較できる。
+pub struct RollingHash {
+    base: u64,
+    pow: Vec<u64>,
+    pre: Vec<u64>,
+}
+
+/// 部分列のハッシュ値と長さ。
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct HashValue {
+    hash: u64,
+    len: usize,
+}
+
+impl HashValue {
+    pub fn len(self) -> usize { self.len }
+    pub fn is_empty(self) -> bool { self.len == 0 }
+}
+
+impl RollingHash {
+    /// 基数をランダムに選ぶ。
+    pub fn new<T: Hash>(buf: &[T]) -> Self {
+        let base = RandomState::new().build_hasher().finish() % (MOD - 2) + 2;
+        Self::with_base(buf, base)
+    }
+
+    pub fn with_base<T: Hash>(buf: &[T], base: u64) -> Self {
+        assert!((2..MOD).contains(&base), "base out of range");
+        let mut pow = vec![1];
+        let mut pre = vec![0];
+        for x in buf {
+            let mut hasher = DefaultHasher::new();
+            x.hash(&mut hasher);
+            let x = hasher.finish() % MOD;
+            pre.push(add(mul(*pre.last().unwrap(), base), x));
+            pow.push(mul(*pow.last().unwrap(), base));
+        }
+        Self { base, pow, pre }
+    }
+
+    pub fn len(&self) -> usize { self.pre.len() - 1 }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// `range` の部分列のハッシュ値。
+    pub fn hash(&self, range: impl RangeBounds<usize>) -> HashValue {
+        let Range { start, end } = range.to_range(self.len());
+        let sub = mul(self.pre[start], self.pow[end - start]);
+        let hash = add(self.pre[end], MOD - sub);
+        HashValue { hash, len: end - start }
+    }
+
+    /// 連結 `a + b` のハッシュ値。
+    pub fn concat(&self, a: HashValue, b: HashValue) -> HashValue {
+        let hash = add(mul(a.hash, self.pow(b.len)), b.hash);
+        HashValue { hash, len: a.len + b.len }
+    }
+
+    /// `i` 番目からの接尾辞と `j` 番目からの接尾辞の最長共通接頭辞の長さ。
+    pub fn lcp(&self, i: usize, j: usize) -> usize {
+        let max = self.len() - i.max(j);
+        (0..max + 1).bisect(|&k| self.hash(i..i + k) == self.hash(j..j + k)) - 1
+    }
+
+    fn pow(&self, exp: usize) -> u64 {
+        if let Some(&res) = self.pow.get(exp) {
+            return res;
+        }
+        let (mut res, mut base, mut exp) = (1, self.base, exp);
+        while exp > 0 {
+            if exp & 1 != 0 {
+                res = mul(res, base);
+            }
+            base = mul(base, base);
+            exp >>= 1;
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::*;
+
+    fn random_strings() -> Vec<Vec<u8>> {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut res = vec![vec![], b"a".to_vec(), b"abracadabra".to_vec()];
+        for sigma in [1, 2, 3] {
+            for len in [5, 20, 60] {
+                res.push(
+                    (0..len)
+                        .map(|_| rng.gen_range(b'a'..b'a' + sigma))
+                        .collect(),
+                );
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn z_and_prefix_function() {
+        assert_eq!(b"aabcaab".z_array(), [7, 1, 0, 0, 3, 1, 0]);
+        assert_eq!(b"aabcaab".prefix_function(), [0, 1, 0, 0, 1, 2, 3]);
+        for s in random_strings() {
+            let n = s.len();
+            let lcp = |i: usize| {
+                s[i..].iter().zip(&s).take_while(|(x, y)| x == y).count()
+            };
+            let z: Vec<_> = (0..n).map(lcp).collect();
+            assert_eq!(s.z_array(), z);
+            let pi: Vec<_> = (0..n)
+                .map(|i| {
+                    (0..=i).rev().find(|&k| s[..k] == s[i + 1 - k..=i]).unwrap()
+                })
+                .collect();
+            assert_eq!(s.prefix_function(), pi);
+        }
+    }
+
+    #[test]
+    fn kmp() {
+        let text = random_strings();
+        for pat in [&b""[..], b"a", b"ab", b"aba", b"abra", b"aaa"] {
+            let kmp = Kmp::new(pat);
+            for s in &text {
+                let expected: Vec<_> = (0..=s.len())
+                    .filter(|&i| s[i..].starts_with(pat))
+                    .collect();
+                assert_eq!(kmp.find_all(s), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn manacher() {
+        assert_eq!(b"abaab".manacher(), [0, 1, 0, 3, 0, 1, 4, 1, 0, 1, 0]);
+        for s in random_strings() {
+            let n = s.len();
+            let is_pal =
+                |l: usize, r: usize| s[l..r].iter().eq(s[l..r].iter().rev());
+            let expected: Vec<_> = (0..=2 * n)
+                .map(|i| {
+                    (0..=i / 2)
+                        .filter(|&l| i - l <= n && is_pal(l, i - l))
+                        .map(|l| i - 2 * l)
+                        .max()
+                        .unwrap()
+                })
+                .collect();
+            assert_eq!(s.manacher(), expected);
+        }
+    }
+
+    #[test]
+    fn lyndon() {
+        let is_lyndon = |s: &[u8]| (1..s.len()).all(|i| s < &s[i..]);
+        assert_eq!(
+            b"abracadabra".lyndon_factorization(),
+            [0..7, 7..10, 10..11]
+        );
+        for s in random_strings() {
+            let fs = s.lyndon_factorization();
+            assert_eq!(fs.iter().map(|r| r.len()).sum::<usize>(), s.len());
+            for w in fs.windows(2) {
+                assert_eq!(w[0].end, w[1].start);
+                assert!(s[w[0].clone()] >= s[w[1].clone()]);
+            }
+            assert!(fs.iter().all(|r| is_lyndon(&s[r.clone()])));
+
+            let n = s.len();
+            let rot = |k: usize| [&s[k..], &s[..k]].concat();
+            let k = (0..n).min_by_key(|&k| rot(k)).unwrap_or(0);
+            assert_eq!(s.min_rotation(), k);
+        }
+    }
+
+    #[test]
+    fn rolling_hash() {
+        for s in random_strings() {
+            let n = s.len();
+            let rh = RollingHash::new(&s);
+            let rh_fixed = RollingHash::with_base(&s, 1_000_003);
+            for i in 0..=n {
+                for j in 0..=n {
+                    let lcp = s[i..]
+                        .iter()
+                        .zip(&s[j..])
+                        .take_while(|(x, y)| x == y)
+                        .count();
+                    assert_eq!(rh.lcp(i, j), lcp);
+                    assert_eq!(rh_fixed.lcp(i, j), lcp);
+                }
+            }
+            for l in 0..=n {
+                for m in l..=n {
+                    for r in m..=n {
+                        let whole = rh.hash(l..r);
+                        assert_eq!(
+                            rh.concat(rh.hash(l..m), rh.hash(m..r)),
+                            whole
+                        );
+                        assert_eq!(whole.len(), r - l);
+                    }
+                }
+            }
+        }
+        let rh = RollingHash::new(b"ab");
+        let a = rh.hash(..1);
+        let mut long = rh.hash(..0);
+        for _ in 0..5 {
+            long = rh.concat(long, a);
+        }
+        let rh5 = RollingHash::with_base(b"aaaaa", rh.base);
+        assert_eq!(long, rh5.hash(..));
+        assert_ne!(rh.hash(..1), rh.hash(1..));
+    }
+}
@@ -1,9 +1,11 @@
 use std::{
     cmp::Ordering::{Equal, Greater, Less},
     collections::{BTreeMap, BTreeSet},
-    ops::Index,
+    ops::{Index, Range},
 };
 
+use binary_io::{BinaryIo, BinaryIoError, Decoder, Encoder};
+
 const NONE: usize = 1_usize.wrapping_neg();
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -15,7 +17,7 @@ pub struct SuffixArray<T: Ord> {
 impl<T: Ord> From<Vec<T>> for SuffixArray<T> {
     fn from(buf: Vec<T>) -> Self {
         let buf_usize = hash(&buf);
-        let sa = sa_is(&buf_usize);
+        let sa = sa_is(&buf_usize, buf_usize.len());
         Self { buf, sa }
     }
 }
@@ -30,7 +32,7 @@ impl From<String> for SuffixArray<char> {
 impl SuffixArray<u8> {
     pub fn from_bytes(buf: Vec<u8>) -> Self {
         let buf_usize = hash_bytes(&buf);
-        let sa = sa_is(&buf_usize);
+        let sa = sa_is(&buf_usize, buf_usize.len());
         Self { buf, sa }
     }
 }
@@ -38,7 +40,7 @@ impl SuffixArray<u8> {
 impl SuffixArray<char> {
     pub fn from_chars(buf: Vec<char>) -> Self {
         let buf_usize = hash_chars(&buf);
-        let sa = sa_is(&buf_usize);
+        let sa = sa_is(&buf_usize, buf_usize.len());
         Self { buf, sa }
     }
 }
@@ -46,9 +48,18 @@ impl SuffixArray<char> {
 impl SuffixArray<usize> {
     pub fn from_hashed(buf: Vec<usize>) -> Self {
         assert!(Self::is_hashed(&buf));
+        let sigma = buf.len();
+        Self::from_ranked(buf, sigma)
+    }
+
+    /// 各要素が `0..sigma` に含まれる列から構築する。
+    ///
+    /// 座標圧縮や [`from_hashed`](Self::from_hashed) の検査を行わないので、
+    /// 時間計算量は $O(n+\\sigma)$ となる。範囲外の要素があれば panic する。
+    pub fn from_ranked(buf: Vec<usize>, sigma: usize) -> Self {
         let buf_usize: Vec<_> =
             buf.iter().map(|x| x + 1).chain(Some(0)).collect();
-        let sa = sa_is(&buf_usize);
+        let sa = sa_is(&buf_usize, sigma + 1);
         Self { buf, sa }
     }
 
@@ -113,22 +124,14 @@ enum LsType {
     S(bool), // is leftmost S-type
 }
 
-fn count_freq(buf: &[usize]) -> Vec<usize> {
-    let mut res = vec![0; buf.len()];
+fn count_freq(buf: &[usize], sigma: usize) -> Vec<usize> {
+    let mut res = vec![0; sigma];
     for &x in buf {
         res[x] += 1;
     }
     res
 }
 
-fn inv_perm(buf: &[usize]) -> Vec<usize> {
-    let mut res = vec![0; buf.len()];
-    for (i, &x) in buf.iter().enumerate() {
-        res[x] = i;
-    }
-    res
-}
-
 fn ls_classify(buf: &[usize]) -> Vec<LsType> {
     let mut res = vec![LsType::S(false); buf.len()];
     for i in (0..buf.len() - 1).rev() {
@@ -176,7 +179,7 @@ fn induce(buf: &[usize], sa: &mut [usize], count: &[usize], ls: &[LsType]) {
         }
     }
     let mut tail = bucket_tail(count);
-    for i in (1..count.len()).rev() {
+    for i in (1..sa.len()).rev() {
         let j = sa[i];
         if j <= buf.len() {
             if j > 0 && ls[j - 1] != LsType::L {
@@ -221,11 +224,16 @@ fn reduce(buf: &[usize], lms: &[usize], ls: &[LsType]) -> Vec<usize> {
         .collect()
 }
 
-fn sa_is(buf: &[usize]) -> Vec<usize> {
+fn sa_is(buf: &[usize], sigma: usize) -> Vec<usize> {
     let len = buf.len();
-    let count = count_freq(buf);
-    if count.iter().all(|&x| x == 1) {
-        return inv_perm(buf);
+    let count = count_freq(buf, sigma);
+    if count.iter().all(|&x| x <= 1) {
+        let head = bucket_head(&count);
+        let mut sa = vec![0; len];
+        for (i, &x) in buf.iter().enumerate() {
+            sa[head[x]] = i;
+        }
+        return sa;
     }
 
     let ls = ls_classify(buf);
@@ -241,7 +249,7 @@ fn sa_is(buf: &[usize]) -> Vec<usize> {
     // lexicographic order
     let lms: Vec<_> =
         sa.into_iter().filter(|&i| ls[i] == LsType::S(true)).collect();
-    let rs_sa = sa_is(&reduce(buf, &lms, &ls));
+    let rs_sa = sa_is(&reduce(buf, &lms, &ls), lms.len());
 
     // appearing order
     let lms: Vec<_> = (0..len).filter(|&i| ls[i] == LsType::S(true)).collect();
@@ -260,37 +268,9 @@ fn sa_is(buf: &[usize]) -> Vec<usize> {
 
 impl<T: Ord> SuffixArray<T> {
     pub fn search(&self, pat: &[T]) -> impl Iterator<Item = usize> + '_ {
-        let lo = {
-            let mut lt = 1_usize.wrapping_neg();
-            let mut ge = self.sa.len();
-            while ge.wrapping_sub(lt) > 1 {
-                let mid = lt.wrapping_add(ge.wrapping_sub(lt) / 2);
-                let pos = self.sa[mid];
-                match self.buf[pos..].cmp(pat) {
-                    Less => lt = mid,
-                    _ => ge = mid,
-                }
-            }
-            ge
-        };
-        if lo >= self.sa.len() {
-            return self.sa[lo..lo].iter().copied();
-        }
-        let hi = {
-            let mut le = lo.wrapping_sub(1);
-            let mut gt = self.sa.len();
-            while gt.wrapping_sub(le) > 1 {
-                let mid = le.wrapping_add(gt.wrapping_sub(le) / 2);
-                let pos = self.sa[mid];
-                let len = pat.len().min(self.buf[pos..].len());
-                match self.buf[pos..pos + len].cmp(pat) {
-                    Greater => gt = mid,
-                    _ => le = mid,
-                }
-            }
-            gt
-        };
-        self.sa[lo..hi].iter().copied()
+        let range =
+            search_range(self.sa.len(), |i| &self.buf[self.sa[i]..], pat);
+        self.sa[range].iter().copied()
     }
 
     pub fn lcpa(&self) -> Vec<usize> {
@@ -317,9 +297,47 @@ impl<T: Ord> SuffixArray<T> {
         res
     }
 
+    pub fn text(&self) -> &[T] { &self.buf }
     pub fn into_inner(self) -> Vec<usize> { self.sa }
 }
 
+fn search_range<'a, T: Ord + 'a>(
+    len: usize,
+    suffix: impl Fn(usize) -> &'a [T],
+    pat: &[T],
+) -> Range<usize> {
+    let lo = {
+        let mut lt = 1_usize.wrapping_neg();
+        let mut ge = len;
+        while ge.wrapping_sub(lt) > 1 {
+            let mid = lt.wrapping_add(ge.wrapping_sub(lt) / 2);
+            match suffix(mid).cmp(pat) {
+                Less => lt = mid,
+                _ => ge = mid,
+            }
+        }
+        ge
+    };
+    if lo >= len {
+        return lo..lo;
+    }
+    let hi = {
+        let mut le = lo.wrapping_sub(1);
+        let mut gt = len;
+        while gt.wrapping_sub(le) > 1 {
+            let mid = le.wrapping_add(gt.wrapping_sub(le) / 2);
+            let suf = suffix(mid);
+            let len = pat.len().min(suf.len());
+            match suf[..len].cmp(pat) {
+                Greater => gt = mid,
+                _ => le = mid,
+            }
+        }
+        gt
+    };
+    lo..hi
+}
+
 impl SuffixArray<char> {
     pub fn search_str(&self, pat: &str) -> impl Iterator<Item = usize> + '_ {
         let pat: Vec<_> = pat.chars().collect();
@@ -332,6 +350,125 @@ impl<T: Ord> Index<usize> for SuffixArray<T> {
     fn index(&self, i: usize) -> &usize { &self.sa[i] }
 }
 
+impl<T: Ord + BinaryIo> BinaryIo for SuffixArray<T> {
+    fn tag() -> String { format!("SuffixArray<{}>", T::tag()) }
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put(&self.buf);
+        enc.put(&self.sa);
+    }
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, BinaryIoError> {
+        let buf: Vec<T> = dec.get()?;
+        let sa: Vec<usize> = dec.get()?;
+        let mut seen = vec![false; sa.len()];
+        let is_perm = sa.len() == buf.len() + 1
+            && sa.iter().all(|&i| {
+                i < seen.len() && !std::mem::replace(&mut seen[i], true)
+            });
+        if !is_perm {
+            return Err(BinaryIoError::Invalid("suffix array"));
+        }
+        Ok(Self { buf, sa })
+    }
+}
+
+/// 複数の文字列の接尾辞配列。
+///
+/// 各要素は (文字列の番号, 開始位置) であり、空の接尾辞も含む。
+/// 内容が等しい接尾辞は、文字列の番号の昇順に並ぶ。
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GeneralizedSuffixArray<T: Ord> {
+    bufs: Vec<Vec<T>>,
+    sa: Vec<(usize, usize)>,
+}
+
+impl<T: Ord> From<Vec<Vec<T>>> for GeneralizedSuffixArray<T> {
+    fn from(bufs: Vec<Vec<T>>) -> Self {
+        let enc: BTreeMap<_, _> = {
+            let seen: BTreeSet<_> = bufs.iter().flatten().collect();
+            seen.into_iter().zip(0..).collect()
+        };
+        // s[0] $[0] s[1] $[1] ... s[k-1] $[k-1] $, where $ < $[0] < ...
+        // < $[k-1] < (other characters).
+        let k = bufs.len();
+        let mut buf_usize = vec![];
+        let mut pos = vec![];
+        for (i, buf) in bufs.iter().enumerate() {
+            buf_usize.extend(buf.iter().map(|x| enc[x] + k + 1));
+            buf_usize.push(i + 1);
+            pos.extend((0..=buf.len()).map(|j| (i, j)));
+        }
+        buf_usize.push(0);
+        let sigma = enc.len() + k + 1;
+        let sa =
+            sa_is(&buf_usize, sigma)[1..].iter().map(|&i| pos[i]).collect();
+        Self { bufs, sa }
+    }
+}
+
+impl<T: Ord> GeneralizedSuffixArray<T> {
+    pub fn len(&self) -> usize { self.sa.len() }
+    pub fn is_empty(&self) -> bool { self.sa.is_empty() }
+    pub fn num_texts(&self) -> usize { self.bufs.len() }
+    pub fn text(&self, id: usize) -> &[T] { &self.bufs[id] }
+
+    /// `pat` を接頭辞に持つ接尾辞を昇順に返す。
+    pub fn search(
+        &self,
+        pat: &[T],
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let range = search_range(self.sa.len(), |i| self.suffix(i), pat);
+        self.sa[range].iter().copied()
+    }
+
+    fn suffix(&self, i: usize) -> &[T] {
+        let (id, pos) = self.sa[i];
+        &self.bufs[id][pos..]
+    }
+
+    /// `res[i]` は `i - 1` 番目と `i` 番目の接尾辞の最長共通接頭辞長。
+    /// ただし `res[0] = 0` とする。
+    pub fn lcpa(&self) -> Vec<usize> {
+        let mut rank: Vec<_> =
+            self.bufs.iter().map(|buf| vec![0; buf.len() + 1]).collect();
+        for (i, &(id, pos)) in self.sa.iter().enumerate() {
+            rank[id][pos] = i;
+        }
+        let mut res = vec![0; self.sa.len()];
+        for (id, buf) in self.bufs.iter().enumerate() {
+            let mut h = 0_usize;
+            for i in 0..buf.len() {
+                let r = rank[id][i];
+                let (jd, j) = self.sa[r - 1];
+                let other = &self.bufs[jd];
+                h = h.saturating_sub(1);
+                while i + h < buf.len() && j + h < other.len() {
+                    if buf[i + h] != other[j + h] {
+                        break;
+                    }
+                    h += 1;
+                }
+                res[r] = h;
+            }
+        }
+        res
+    }
+
+    pub fn into_inner(self) -> Vec<(usize, usize)> { self.sa }
+}
+
+impl From<Vec<String>> for GeneralizedSuffixArray<char> {
+    fn from(bufs: Vec<String>) -> Self {
+        let bufs: Vec<Vec<_>> =
+            bufs.iter().map(|s| s.chars().collect()).collect();
+        bufs.into()
+    }
+}
+
+impl<T: Ord> Index<usize> for GeneralizedSuffixArray<T> {
+    type Output = (usize, usize);
+    fn index(&self, i: usize) -> &(usize, usize) { &self.sa[i] }
+}
+
 #[test]
 fn sanity_check() {
     let buf = b"abracadabra".to_vec();
@@ -366,3 +503,84 @@ fn worst_case() {
 
     assert_eq!(actual.sa, expected);
 }
+
+#[test]
+fn binary_io() {
+    let sa = SuffixArray::from("mississippi".to_owned());
+    let mut buf = vec![];
+    sa.write_to(&mut buf).unwrap();
+    let actual = SuffixArray::<char>::read_from(&mut buf.as_slice()).unwrap();
+    assert_eq!(actual, sa);
+    assert!(actual.search_str("ssi").eq([5, 2]));
+
+    let res = SuffixArray::<u8>::read_from(&mut buf.as_slice());
+    assert!(matches!(res, Err(BinaryIoError::Tag { .. })));
+    let res = SuffixArray::<char>::read_from(&mut &buf[..buf.len() / 2]);
+    assert!(matches!(res, Err(BinaryIoError::Truncated)));
+}
+
+#[test]
+fn ranked() {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    let mut rng = ChaCha20Rng::from_seed([0; 32]);
+    let mut rand = |n: usize| rng.gen_range(0..n);
+    for sigma in [1, 2, 3, 10, 1000] {
+        for len in [0, 1, 5, 30, 200] {
+            let buf: Vec<_> = (0..len).map(|_| rand(sigma)).collect();
+            let actual = SuffixArray::from_ranked(buf.clone(), sigma);
+            let expected = SuffixArray::from(buf);
+            assert_eq!(actual, expected);
+        }
+    }
+}
+
+#[test]
+fn generalized() {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    let mut rng = ChaCha20Rng::from_seed([0; 32]);
+    let mut rand = |n: usize| rng.gen_range(0..n);
+    for _ in 0..100 {
+        let k = rand(5);
+        let bufs: Vec<Vec<_>> =
+            (0..k).map(|_| (0..rand(10)).map(|_| rand(3)).collect()).collect();
+        let gsa = GeneralizedSuffixArray::from(bufs.clone());
+
+        let mut expected: Vec<_> = (0..k)
+            .flat_map(|i| (0..=bufs[i].len()).map(move |j| (i, j)))
+            .collect();
+        expected.sort_by_key(|&(i, j)| (&bufs[i][j..], i));
+        assert_eq!(gsa.len(), expected.len());
+        assert!((0..gsa.len()).all(|i| gsa[i] == expected[i]));
+
+        let lcpa = gsa.lcpa();
+        assert_eq!(lcpa.first().copied().unwrap_or(0), 0);
+        for i in 1..expected.len() {
+            let (i0, j0) = expected[i - 1];
+            let (i1, j1) = expected[i];
+            let lcp = bufs[i0][j0..]
+                .iter()
+                .zip(&bufs[i1][j1..])
+                .take_while(|(x, y)| x == y)
+                .count();
+            assert_eq!(lcpa[i], lcp);
+        }
+
+        let pat = [rand(3), rand(3)];
+        let expected_search: Vec<_> = expected
+            .iter()
+            .copied()
+            .filter(|&(i, j)| bufs[i][j..].starts_with(&pat))
+            .collect();
+        assert!(gsa.search(&pat).eq(expected_search));
+    }
+
+    let gsa = GeneralizedSuffixArray::from(vec![
+        "abra".to_owned(),
+        "cadabra".to_owned(),
+    ]);
+    assert!(gsa.search(&['b', 'r', 'a']).eq([(0, 1), (1, 4)]));
+}
@@ -0,0 +1,226 @@
+use std::{collections::BTreeMap, ops::Range};
+
+const NONE: usize = 1_usize.wrapping_neg();
+
+/// 接尾辞オートマトン。
+///
+/// 文字列 $S$ の部分文字列全体を受理する最小の DFA を、末尾への文字の
+/// 追加に対して償却 $O(\\log\\sigma)$ 時間で更新する。状態 `0` が初期状態。
+/// 各状態は終端位置の集合 (endpos) が等しい部分文字列の集合を表し、
+/// その長さは `min_len(s)..=max_len(s)` となる。
+pub struct SuffixAutomaton<T> {
+    next: Vec<BTreeMap<T, usize>>,
+    link: Vec<usize>,
+    len: Vec<usize>,
+    // whether the state was created for a prefix, i.e. not cloned.
+    is_prefix: Vec<bool>,
+    last: usize,
+    distinct: usize,
+}
+
+impl<T: Ord + Clone> SuffixAutomaton<T> {
+    pub fn new() -> Self {
+        Self {
+            next: vec![BTreeMap::new()],
+            link: vec![NONE],
+            len: vec![0],
+            is_prefix: vec![false],
+            last: 0,
+            distinct: 0,
+        }
+    }
+
+    pub fn push(&mut self, x: T) {
+        let cur = self.new_state(self.len[self.last] + 1, true);
+        let mut p = self.last;
+        while p != NONE && !self.next[p].contains_key(&x) {
+            self.next[p].insert(x.clone(), cur);
+            p = self.link[p];
+        }
+        if p == NONE {
+            self.link[cur] = 0;
+        } else {
+            let q = self.next[p][&x];
+            if self.len[p] + 1 == self.len[q] {
+                self.link[cur] = q;
+            } else {
+                let clone = self.new_state(self.len[p] + 1, false);
+                self.next[clone] = self.next[q].clone();
+                self.link[clone] = self.link[q];
+                while p != NONE && self.next[p].get(&x) == Some(&q) {
+                    self.next[p].insert(x.clone(), clone);
+                    p = self.link[p];
+                }
+                self.link[q] = clone;
+                self.link[cur] = clone;
+            }
+        }
+        self.distinct += self.len[cur] - self.len[self.link[cur]];
+        self.last = cur;
+    }
+
+    fn new_state(&mut self, len: usize, is_prefix: bool) -> usize {
+        self.next.push(BTreeMap::new());
+        self.link.push(NONE);
+        self.len.push(len);
+        self.is_prefix.push(is_prefix);
+        self.len.len() - 1
+    }
+}
+
+impl<T: Ord> SuffixAutomaton<T> {
+    /// これまでに追加した文字列の長さ。
+    pub fn len(&self) -> usize { self.len[self.last] }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    pub fn num_states(&self) -> usize { self.len.len() }
+
+    pub fn transition(&self, s: usize, x: &T) -> Option<usize> {
+        self.next[s].get(x).copied()
+    }
+    pub fn link(&self, s: usize) -> Option<usize> {
+        Some(self.link[s]).filter(|&t| t != NONE)
+    }
+    pub fn max_len(&self, s: usize) -> usize { self.len[s] }
+    pub fn min_len(&self, s: usize) -> usize {
+        self.link(s).map(|t| self.len[t] + 1).unwrap_or(0)
+    }
+    /// 文字列全体を表す状態。
+    pub fn last(&self) -> usize { self.last }
+
+    /// 空でない相異なる部分文字列の個数。
+    pub fn count_distinct_substrings(&self) -> usize { self.distinct }
+
+    /// 各状態の表す部分文字列の出現回数 (endpos の大きさ)。
+    pub fn occurrences(&self) -> Vec<usize> {
+        let n = self.num_states();
+        let mut res: Vec<_> =
+            self.is_prefix.iter().map(|&b| b as usize).collect();
+        res[0] = self.len() + 1;
+        let mut ord: Vec<_> = (1..n).collect();
+        ord.sort_unstable_by_key(|&s| std::cmp::Reverse(self.len[s]));
+        for s in ord {
+            let t = self.link[s];
+            if t != 0 {
+                res[t] += res[s];
+            }
+        }
+        res
+    }
+
+    /// `pat` を表す状態。`pat` が部分文字列でなければ `None`。
+    pub fn find(&self, pat: &[T]) -> Option<usize> {
+        pat.iter().try_fold(0, |s, x| self.transition(s, x))
+    }
+
+    /// `other` の部分文字列であって、これまでに追加した文字列の部分文字列
+    /// でもあるもののうち、最長のものの `other` での範囲。
+    pub fn longest_common_substring(&self, other: &[T]) -> Range<usize> {
+        let (mut s, mut len) = (0, 0);
+        let mut res = 0..0;
+        for (i, x) in other.iter().enumerate() {
+            while s != 0 && !self.next[s].contains_key(x) {
+                s = self.link[s];
+                len = self.len[s];
+            }
+            if let Some(&t) = self.next[s].get(x) {
+                s = t;
+                len += 1;
+            }
+            if len > res.len() {
+                res = i + 1 - len..i + 1;
+            }
+        }
+        res
+    }
+}
+
+impl<T: Ord + Clone> Default for SuffixAutomaton<T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T: Ord + Clone> From<Vec<T>> for SuffixAutomaton<T> {
+    fn from(buf: Vec<T>) -> Self {
+        let mut res = Self::new();
+        for x in buf {
+            res.push(x);
+        }
+        res
+    }
+}
+
+impl From<String> for SuffixAutomaton<char> {
+    fn from(buf: String) -> Self { buf.chars().collect::<Vec<_>>().into() }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+    use std::collections::BTreeSet;
+
+    use crate::*;
+
+    #[test]
+    fn sanity_check() {
+        let sam = SuffixAutomaton::from("abcbc".to_owned());
+        assert_eq!(sam.len(), 5);
+        assert_eq!(sam.count_distinct_substrings(), 12);
+        let occ = sam.occurrences();
+        let bc = sam.find(&['b', 'c']).unwrap();
+        assert_eq!(occ[bc], 2);
+        assert_eq!(sam.find(&['c', 'a']), None);
+        assert_eq!(occ[sam.find(&[]).unwrap()], 6);
+        let other: Vec<_> = "xxcbcbyy".chars().collect();
+        assert_eq!(sam.longest_common_substring(&other), 2..5);
+        assert_eq!(sam.longest_common_substring(&[]), 0..0);
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut rand = |n: usize| rng.gen_range(0..n);
+        for sigma in [1, 2, 3, 26] {
+            let mut sam = SuffixAutomaton::new();
+            let mut text = vec![];
+            for _ in 0..60 {
+                let x = rand(sigma);
+                sam.push(x);
+                text.push(x);
+                let n = text.len();
+                assert!(sam.num_states() < 2 * n.max(2));
+
+                let substrings: BTreeSet<_> = (0..n)
+                    .flat_map(|l| (l + 1..=n).map(move |r| l..r))
+                    .map(|r| &text[r])
+                    .collect();
+                assert_eq!(sam.count_distinct_substrings(), substrings.len());
+            }
+
+            let n = text.len();
+            let occ = sam.occurrences();
+            for l in 0..n {
+                for r in l + 1..=n {
+                    let pat = &text[l..r];
+                    let s = sam.find(pat).unwrap();
+                    assert!(
+                        (sam.min_len(s)..=sam.max_len(s)).contains(&pat.len())
+                    );
+                    let count =
+                        text.windows(pat.len()).filter(|&w| w == pat).count();
+                    assert_eq!(occ[s], count);
+                }
+            }
+            assert_eq!(sam.find(&[sigma]), None);
+
+            let other: Vec<_> = (0..40).map(|_| rand(sigma + 1)).collect();
+            let actual = sam.longest_common_substring(&other);
+            let is_common = |r: Range<usize>| {
+                text.windows(r.len()).any(|w| w == &other[r.clone()])
+            };
+            assert!(is_common(actual.clone()));
+            let m = other.len();
+            assert!((0..m).all(|l| l + actual.len() >= m
+                || !is_common(l..l + actual.len() + 1)));
+        }
+    }
+}
@@ -1,5 +1,9 @@
 use std::ops::Range;
 
+use binary_io::{BinaryIo, BinaryIoError, Decoder, Encoder};
+#[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+use pdep_pext::Pdep;
+
 const W: usize = u64::BITS as usize;
 const BLOCK_LEN: usize = 8;
 const BLOCK: u64 = !(!0 << BLOCK_LEN);
@@ -32,6 +36,62 @@ pub fn select(w: u8, i: usize) -> usize {
     popcnt(gt_eq(splat(i as _), accumulate(expand(w))))
 }
 
+/// `w` において `k` 番目 (0-indexed) に立っているビットの位置を返す。
+///
+/// `target_feature = "bmi2"` が有効なら `pdep` と `tzcnt` で求め、
+/// そうでなければ broadword に求める。
+#[inline(always)]
+pub fn select_in_word(w: u64, k: usize) -> usize {
+    debug_assert!(k < w.count_ones() as usize);
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+    {
+        (1_u64 << k).pdep(w).trailing_zeros() as _
+    }
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+    {
+        select_in_word_broadword(w, k)
+    }
+}
+
+#[cfg_attr(
+    all(target_arch = "x86_64", target_feature = "bmi2"),
+    allow(dead_code)
+)]
+#[inline(always)]
+fn select_in_word_broadword(w: u64, k: usize) -> usize {
+    let mut s = w - (w >> 1 & 0x5555555555555555);
+    s = (s & 0x3333333333333333) + (s >> 2 & 0x3333333333333333);
+    s = (s + (s >> 4)) & 0x0F0F0F0F0F0F0F0F;
+    // the number of ones in bytes `0..=i`
+    let acc = accumulate(s);
+    let byte = popcnt(gt_eq(splat(k as _), acc));
+    let below = get(shift(acc), byte);
+    BLOCK_LEN * byte + select((w >> (BLOCK_LEN * byte)) as u8, k - below)
+}
+
+/// `buf` を並べたビット列において、各 `p` に対して `[0, p)` にある
+/// `1` の個数を返す。`pos` は広義単調増加とする。
+pub fn rank_bulk(buf: &[u64], pos: &[usize]) -> Vec<usize> {
+    assert!(pos.windows(2).all(|w| w[0] <= w[1]), "not monotone");
+    let mut res = Vec::with_capacity(pos.len());
+    let mut acc = 0;
+    let mut j = 0;
+    for &p in pos {
+        for &w in &buf[j..p / W] {
+            acc += w.count_ones() as usize;
+        }
+        j = j.max(p / W);
+        let o = p % W;
+        let part = if o == 0 {
+            0
+        } else {
+            (buf[j] & !(!0 << o)).count_ones() as usize
+        };
+        res.push(acc + part);
+    }
+    res
+}
+
 pub const fn const_rank_table<const LEN: usize, const PAT: usize>()
 -> [[u8; LEN]; PAT] {
     let mut res = [[0; LEN]; PAT];
@@ -72,6 +132,9 @@ pub const fn const_select_table<const LEN: usize, const PAT: usize>()
 
 #[cfg(test)]
 mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
     use crate::*;
 
     const RANK_TABLE: [[u8; 8]; 256] = const_rank_table::<8, 256>();
@@ -94,6 +157,58 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_int_vec_binary_io() {
+        let mut iv = IntVec::new(13);
+        for i in 0..100 {
+            iv.push(i * i % (1 << 13));
+        }
+        let mut buf = vec![];
+        iv.write_to(&mut buf).unwrap();
+
+        let iv = IntVec::read_from(&mut buf.as_slice()).unwrap();
+        assert_eq!(iv.len(), 100);
+        for i in 0..100 {
+            assert_eq!(iv.get::<true>(i as _), i * i % (1 << 13));
+        }
+        let res = IntVec::read_from(&mut &buf[..buf.len() - 9]);
+        assert!(matches!(res, Err(BinaryIoError::Truncated)));
+    }
+
+    #[test]
+    fn test_select_in_word() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut words = vec![0, !0, 1, 1 << 63, 0x8000000000000001];
+        for _ in 0..1000 {
+            let w: u64 = rng.gen();
+            words.extend([w, w & rng.gen::<u64>(), w | rng.gen::<u64>()]);
+        }
+        for w in words {
+            let ones = (0..W).filter(|&i| w >> i & 1 != 0);
+            for (k, i) in ones.enumerate() {
+                assert_eq!(select_in_word(w, k), i);
+                assert_eq!(select_in_word_broadword(w, k), i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rank_bulk() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let buf: Vec<u64> = (0..10).map(|_| rng.gen()).collect();
+        let naive = |p: usize| {
+            (0..p).filter(|&i| buf[i / W] >> (i % W) & 1 != 0).count()
+        };
+        let pos: Vec<_> = (0..=10 * W).collect();
+        let expected: Vec<_> = pos.iter().map(|&p| naive(p)).collect();
+        assert_eq!(rank_bulk(&buf, &pos), expected);
+
+        let pos = [0, 0, 63, 64, 64, 200, 639, 640];
+        let expected: Vec<_> = pos.iter().map(|&p| naive(p)).collect();
+        assert_eq!(rank_bulk(&buf, &pos), expected);
+        assert_eq!(rank_bulk(&[], &[0]), [0]);
+    }
 }
 
 pub struct IntVec {
@@ -152,6 +267,25 @@ impl IntVec {
     }
 }
 
+impl BinaryIo for IntVec {
+    fn tag() -> String { "IntVec".to_owned() }
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put(&self.unit);
+        enc.put(&self.len);
+        enc.put(&self.buf);
+    }
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, BinaryIoError> {
+        let unit: usize = dec.get()?;
+        let len: usize = dec.get()?;
+        let buf: Vec<u64> = dec.get()?;
+        let bitlen = len.checked_mul(unit);
+        if unit > W || bitlen.map(|b| b.div_ceil(W)) != Some(buf.len()) {
+            return Err(BinaryIoError::Invalid("int vector length"));
+        }
+        Ok(Self { unit, buf, len })
+    }
+}
+
 pub struct RankTable(IntVec);
 pub struct SelectTable(IntVec);
 